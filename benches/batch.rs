@@ -0,0 +1,59 @@
+//! Compares the scalar and the batch conversions of `Props`.
+//! Run with `cargo bench --bench batch`.
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use hexa::axial::Axial;
+use hexa::hex::Props;
+use hexa::Hexagons;
+
+const SAMPLES: usize = 500_000;
+const ROUNDS: u32 = 20;
+
+fn measure(name: &str, mut f: impl FnMut()) -> Duration {
+    f();
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        f();
+    }
+    let per_round = start.elapsed() / ROUNDS;
+    println!("{:<24} {:>10.3} ms  {:>6.2} ns/sample", name, per_round.as_secs_f64() * 1e3, per_round.as_nanos() as f64 / SAMPLES as f64);
+    per_round
+}
+
+fn main() {
+    // a deterministic trail of positions
+    let xy: Vec<(f32, f32)> = (0..SAMPLES)
+        .map(|i| {
+            let t = i as f32 * 0.001f32;
+            (t.sin() * 800f32 + t * 3f32, t.cos() * 600f32 - t * 2f32)
+        })
+        .collect();
+    let mut out = vec![Axial::default(); SAMPLES];
+    let mut refs = vec![(0f32, 0f32); SAMPLES];
+
+    for (name, h) in [("flat", Props::flat(21f32, 24f32)), ("pointy", Props::pointy(24f32, 21f32))] {
+        println!("{}", name);
+        let scalar = measure("  axial", || {
+            for (qr, xy) in out.iter_mut().zip(&xy) {
+                *qr = h.axial(black_box(*xy));
+            }
+            black_box(&out);
+        });
+        let batch = measure("  axial_batch", || {
+            h.axial_batch(black_box(&xy), &mut out);
+            black_box(&out);
+        });
+        println!("  speedup {:.2}", scalar.as_secs_f64() / batch.as_secs_f64());
+        measure("  xy_ref", || {
+            for (r, qr) in refs.iter_mut().zip(&out) {
+                *r = h.xy_ref(black_box(qr));
+            }
+            black_box(&refs);
+        });
+        measure("  xy_ref_batch", || {
+            h.xy_ref_batch(black_box(&out), &mut refs);
+            black_box(&refs);
+        });
+    }
+}
@@ -1,52 +1,232 @@
-/// Following the great page https://www.redblobgames.com/grids/hexagons/
+//! Following the great page https://www.redblobgames.com/grids/hexagons/
 
 use std::ops::{AddAssign, Add, SubAssign, Sub, MulAssign, Mul};
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
-pub struct Axial {
-    q: i32,
-    r: i32
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::cube::FractionalAxial;
+use super::direction::{HexDiagonal, HexDirection};
+use super::int::Int;
+
+/// Axial coordinates of a hexagon.
+/// Lines, iterators and the pixel layouts use the default `i32`,
+/// other widths are meant for storing coordinates and cube distances.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Axial<T = i32> {
+    q: T,
+    r: T
 }
 
-impl Axial {
-    pub fn new(q: impl Into<i32>, r: impl Into<i32>) -> Self {
-        Axial { q: q.into(), r: r.into()}
+impl<T: Int> Axial<T> {
+    /// Creates an `Axial` of any width, `new` is the one for `i32`.
+    pub fn from_qr(q: T, r: T) -> Self {
+        Axial { q, r }
     }
 
     /// Compute the third of the hexagonal cube coordinates.
-    pub fn s(&self) -> i32 {
+    pub fn s(&self) -> T {
         -self.q-self.r
     }
 
-    pub fn length(&self) -> u32 {
-        (self.q.abs() + (self.q+self.r).abs() + self.r.abs()) as u32 / 2
+    /// Like `s`, but `None` if it does not fit into `T`.
+    pub fn checked_s(&self) -> Option<T> {
+        // one of the two orders works whenever the result fits
+        self.q.checked_add(self.r).and_then(T::checked_neg)
+            .or_else(|| self.q.checked_neg()?.checked_sub(self.r))
+    }
+
+    /// Like `s`, but wraps around at the limits of `T`.
+    pub fn wrapping_s(&self) -> T {
+        self.q.wrapping_add(self.r).wrapping_neg()
+    }
+
+    /// Distance to the origin. Never overflows, but saturates if it does not fit
+    /// the unsigned type, which happens only if q and r are both close to `T::MIN` or `T::MAX`.
+    pub fn length(&self) -> T::Unsigned {
+        self.distance_to(Axial::default())
+    }
+
+    /// Distance to `other`, saturating like `length`.
+    pub fn distance_to(&self, other: Self) -> T::Unsigned {
+        self.checked_distance_to(other).unwrap_or(T::UNSIGNED_MAX)
+    }
+
+    /// Like `length`, but `None` if it does not fit the unsigned type.
+    pub fn checked_length(&self) -> Option<T::Unsigned> {
+        self.checked_distance_to(Axial::default())
+    }
+
+    /// Like `distance_to`, but `None` if it does not fit the unsigned type.
+    pub fn checked_distance_to(&self, other: Self) -> Option<T::Unsigned> {
+        T::Unsigned::try_from(self.wide_distance_to(other)).ok()
+    }
+
+    /// Exact distance to `other` in a type twice as wide as `T`, it always fits.
+    pub fn wide_distance_to(&self, other: Self) -> T::Distance {
+        T::cube_distance(self.to_tuple(), other.to_tuple())
+    }
+
+    pub fn checked_add(&self, other: Self) -> Option<Self> {
+        Some(Axial { q: self.q.checked_add(other.q)?, r: self.r.checked_add(other.r)? })
+    }
+
+    pub fn checked_sub(&self, other: Self) -> Option<Self> {
+        Some(Axial { q: self.q.checked_sub(other.q)?, r: self.r.checked_sub(other.r)? })
+    }
+
+    pub fn checked_mul(&self, factor: T) -> Option<Self> {
+        Some(Axial { q: self.q.checked_mul(factor)?, r: self.r.checked_mul(factor)? })
+    }
+
+    pub fn wrapping_add(&self, other: Self) -> Self {
+        Axial { q: self.q.wrapping_add(other.q), r: self.r.wrapping_add(other.r) }
+    }
+
+    pub fn wrapping_sub(&self, other: Self) -> Self {
+        Axial { q: self.q.wrapping_sub(other.q), r: self.r.wrapping_sub(other.r) }
+    }
+
+    pub fn wrapping_mul(&self, factor: T) -> Self {
+        Axial { q: self.q.wrapping_mul(factor), r: self.r.wrapping_mul(factor) }
     }
 
-    pub fn distance_to(&self, other: Self) -> u32 {
-        (*self - other).length()
+    /// Rotate by 60° clockwise around the origin.
+    pub fn rotate_cw(&self) -> Self {
+        Axial { q: -self.r, r: self.q + self.r }
     }
 
-    pub fn lerp(&self, other: Self, t: f32) -> (f32, f32) {
-        let (q1, r1) = *self * (1f32 - t);
-        let (q2, r2) = other * t;
-        (q1+q2, r1+r2)
+    /// Rotate by 60° counter clockwise around the origin.
+    pub fn rotate_ccw(&self) -> Self {
+        Axial { q: self.q + self.r, r: -self.q }
+    }
+
+    /// Mirror at the q axis, keeping q and swapping r and s.
+    pub fn reflect_q(&self) -> Self {
+        Axial { q: self.q, r: self.s() }
+    }
+
+    /// Mirror at the r axis, keeping r and swapping q and s.
+    pub fn reflect_r(&self) -> Self {
+        Axial { q: self.s(), r: self.r }
+    }
+
+    /// Mirror at the s axis, keeping s and swapping q and r.
+    /// Never overflows, unlike the other rotations and reflections.
+    pub fn reflect_s(&self) -> Self {
+        Axial { q: self.r, r: self.q }
+    }
+
+    pub fn checked_rotate_cw(&self) -> Option<Self> {
+        Some(Axial { q: self.r.checked_neg()?, r: self.q.checked_add(self.r)? })
+    }
+
+    pub fn checked_rotate_ccw(&self) -> Option<Self> {
+        Some(Axial { q: self.q.checked_add(self.r)?, r: self.q.checked_neg()? })
+    }
+
+    pub fn checked_reflect_q(&self) -> Option<Self> {
+        Some(Axial { q: self.q, r: self.checked_s()? })
+    }
+
+    pub fn checked_reflect_r(&self) -> Option<Self> {
+        Some(Axial { q: self.checked_s()?, r: self.r })
+    }
+
+    pub fn wrapping_rotate_cw(&self) -> Self {
+        Axial { q: self.r.wrapping_neg(), r: self.q.wrapping_add(self.r) }
+    }
+
+    pub fn wrapping_rotate_ccw(&self) -> Self {
+        Axial { q: self.q.wrapping_add(self.r), r: self.q.wrapping_neg() }
+    }
+
+    pub fn wrapping_reflect_q(&self) -> Self {
+        Axial { q: self.q, r: self.wrapping_s() }
+    }
+
+    pub fn wrapping_reflect_r(&self) -> Self {
+        Axial { q: self.wrapping_s(), r: self.r }
+    }
+
+    pub fn to_tuple(&self) -> (T, T) {
+        (self.q, self.r)
+    }
+
+    /// Converts to another width, `None` if q or r do not fit.
+    /// Lossless conversions to wider types are also available with `From`.
+    pub fn try_cast<U: Int + TryFrom<T>>(&self) -> Option<Axial<U>> {
+        Some(Axial { q: U::try_from(self.q).ok()?, r: U::try_from(self.r).ok()? })
+    }
+
+}
+
+impl Axial {
+    pub fn new(q: impl Into<i32>, r: impl Into<i32>) -> Self {
+        Axial { q: q.into(), r: r.into() }
+    }
+
+    pub fn lerp(&self, other: Self, t: f32) -> FractionalAxial {
+        *self * (1f32 - t) + other * t
     }
 
     pub fn point_on_line(p1: Self, p2: Self, dist: f32) -> Self {
+        if p1 == p2 {
+            return p1;
+        }
         let dist_p1_p2 = p1.distance_to(p2) as f32;
-        Axial::from(p1.lerp(p2, dist/dist_p1_p2))
+        p1.lerp(p2, dist/dist_p1_p2).round()
+    }
+
+    /// All hexagons on the line to `other`, including both ends.
+    /// Lines along an edge between two hexagons always take the same side.
+    pub fn line_to(&self, other: Self) -> LineToHex {
+        LineToHex::new(*self, other, LINE_NUDGE)
+    }
+
+    /// Like `line_to`, but returns both hexagons where the line runs exactly along an edge.
+    pub fn supercover_to(&self, other: Self) -> SupercoverToHex {
+        SupercoverToHex::new(*self, other)
     }
 
     pub fn circle(&self, hex_radius: u32) -> CircleAroundHex {
         CircleAroundHex::new(*self, hex_radius)
     }
 
+    /// The six neighbours clockwise, starting with `HexDirection::RS`.
     pub fn neighbours(&self) -> CircleAroundHex {
         self.circle(1)
     }
 
-    pub fn to_tuple(&self) -> (i32, i32) {
-        (self.q, self.r)
+    pub fn neighbour(&self, dir: HexDirection) -> Self {
+        *self + dir.offset()
+    }
+
+    pub fn diagonal(&self, diag: HexDiagonal) -> Self {
+        *self + diag.offset()
+    }
+
+    /// Direction in which `other` lies, if it is a neighbour.
+    pub fn direction_to(&self, other: Self) -> Option<HexDirection> {
+        HexDirection::between(*self, other)
+    }
+
+    /// All hexagons within `hex_radius`, ring by ring starting with the center.
+    pub fn spiral(&self, hex_radius: u32) -> SpiralAroundHex {
+        SpiralAroundHex::new(*self, hex_radius)
+    }
+
+    /// All hexagons within `hex_radius`, ordered by q and then by r.
+    pub fn range(&self, hex_radius: u32) -> RangeAroundHex {
+        RangeAroundHex::new(*self, hex_radius)
+    }
+
+    /// Rotate by `steps` times 60° around `pivot`, clockwise for positive `steps`.
+    pub fn rotate_around(&self, pivot: Self, steps: i32) -> Self {
+        let mut rel = *self - pivot;
+        for _ in 0..steps.rem_euclid(6) {
+            rel = rel.rotate_cw();
+        }
+        rel + pivot
     }
 
     pub fn to_f32s(&self) -> (f32, f32) {
@@ -55,15 +235,15 @@ impl Axial {
 
 }
 
-impl AddAssign for Axial {
+impl<T: Int> AddAssign for Axial<T> {
     fn add_assign(&mut self, rhs: Self) {
         self.q += rhs.q;
         self.r += rhs.r;
     }
 }
 
-impl Add for Axial {
-    type Output = Axial;
+impl<T: Int> Add for Axial<T> {
+    type Output = Axial<T>;
 
     fn add(self, rhs: Self) -> Self::Output {
         let mut result = self;
@@ -72,7 +252,7 @@ impl Add for Axial {
     }
 }
 
-impl SubAssign for Axial {
+impl<T: Int> SubAssign for Axial<T> {
 
     fn sub_assign(&mut self, rhs: Self) {
         self.q  -= rhs.q;
@@ -80,8 +260,8 @@ impl SubAssign for Axial {
     }
 }
 
-impl Sub for Axial {
-    type Output = Axial;
+impl<T: Int> Sub for Axial<T> {
+    type Output = Axial<T>;
 
     fn sub(self, rhs: Self) -> Self::Output {
         let mut result = self;
@@ -90,19 +270,19 @@ impl Sub for Axial {
     }
 }
 
-impl MulAssign<i32> for Axial {
+impl<T: Int> MulAssign<T> for Axial<T> {
 
-    fn mul_assign(&mut self, rhs: i32) {
+    fn mul_assign(&mut self, rhs: T) {
         self.q *= rhs;
         self.r *= rhs;
     }
 
 }
 
-impl Mul<i32> for Axial {
-    type Output = Axial;
+impl<T: Int> Mul<T> for Axial<T> {
+    type Output = Axial<T>;
 
-    fn mul(self, rhs: i32) -> Self::Output {
+    fn mul(self, rhs: T) -> Self::Output {
         let mut result = self;
         result *= rhs;
         result
@@ -110,10 +290,10 @@ impl Mul<i32> for Axial {
 }
 
 impl Mul<f32> for Axial {
-    type Output = (f32, f32);
+    type Output = FractionalAxial;
 
     fn mul(self, rhs: f32) -> Self::Output {
-        (self.q as f32 * rhs, self.r as f32 * rhs)
+        FractionalAxial::from(self) * rhs
     }
 }
 
@@ -126,42 +306,178 @@ impl Mul<Axial> for i32 {
 }
 
 impl Mul<Axial> for f32 {
-    type Output = (f32, f32);
+    type Output = FractionalAxial;
 
     fn mul(self, rhs: Axial) -> Self::Output {
         rhs * self
     }
 }
 
-impl From<(i32, i32)> for Axial {
-    fn from((q,r): (i32, i32)) -> Self {
-        Axial::new(q, r)
+impl<T: Int> From<(T, T)> for Axial<T> {
+    fn from((q,r): (T, T)) -> Self {
+        Axial { q, r }
     }
 }
 
 impl From<(f32, f32)> for Axial {
     fn from ((q_f, r_f): (f32, f32)) -> Self {
-        let s_f = -q_f - r_f;
+        FractionalAxial::new(q_f, r_f).round()
+    }
+}
+
+impl From<(f64, f64)> for Axial {
+    fn from ((q_f, r_f): (f64, f64)) -> Self {
+        FractionalAxial::new(q_f, r_f).round()
+    }
+}
+
+/// Serialized in the compact form `(q, r)`, use `as_struct` for `{ q, r }`.
+impl<T: Serialize> Serialize for Axial<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (&self.q, &self.r).serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Axial<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (q, r) = <(T, T)>::deserialize(deserializer)?;
+        Ok(Axial { q, r })
+    }
+}
+
+/// Serializes `Axial` as a struct with the fields q and r,
+/// to be used with `#[serde(with = "hexa::axial::as_struct")]`.
+pub mod as_struct {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::Axial;
+
+    #[derive(Deserialize, Serialize)]
+    #[serde(rename = "Axial")]
+    struct Fields<T> {
+        q: T,
+        r: T,
+    }
 
-        let q = q_f.round();
-        let r = r_f.round();
-        let s = s_f.round();
+    pub fn serialize<T: Serialize + Copy, S: Serializer>(qr: &Axial<T>, serializer: S) -> Result<S::Ok, S::Error> {
+        Fields { q: qr.q, r: qr.r }.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, T: Deserialize<'de>, D: Deserializer<'de>>(deserializer: D) -> Result<Axial<T>, D::Error> {
+        let Fields { q, r } = Fields::deserialize(deserializer)?;
+        Ok(Axial { q, r })
+    }
+}
+
+/// Lossless conversions to wider coordinates.
+macro_rules! impl_widen {
+    ($from:ty => $($to:ty),+) => {
+        $(impl From<Axial<$from>> for Axial<$to> {
+            fn from(qr: Axial<$from>) -> Self {
+                Axial { q: qr.q.into(), r: qr.r.into() }
+            }
+        })+
+    };
+}
+
+impl_widen!(i8 => i16, i32, i64);
+impl_widen!(i16 => i32, i64);
+impl_widen!(i32 => i64);
 
-        let q_diff = (q - q_f).abs();
-        let r_diff = (r - r_f).abs();
-        let s_diff = (s - s_f).abs();
+/// Nudge of the line ends, so that lines along edges do not hit ties when rounding.
+pub(crate) const LINE_NUDGE: (f64, f64) = (1e-6, 2e-6);
 
-        let calc_q_b = q_diff > r_diff && q_diff > s_diff;
-        let calc_r = (!calc_q_b && r_diff > s_diff) as i32;
-        let calc_q = calc_q_b as i32;
+/// Line Iterator
+pub struct LineToHex {
+    start: (f64, f64),
+    delta: (f64, f64),
+    steps: u32,
+    idx: u32,
+}
 
-        let q_out = q as i32 * (1-calc_q) - (r as i32 + s as i32) * calc_q;
-        let r_out = r as i32 * (1-calc_r) - (q as i32 + s as i32) * calc_r;
+impl LineToHex {
+    pub(crate) fn new(from: Axial, to: Axial, (nq, nr): (f64, f64)) -> Self {
+        let (q1, r1) = from.to_tuple();
+        let (q2, r2) = to.to_tuple();
+        LineToHex {
+            start: (q1 as f64 + nq, r1 as f64 + nr),
+            delta: (q2 as f64 - q1 as f64, r2 as f64 - r1 as f64),
+            steps: from.distance_to(to),
+            idx: 0,
+        }
+    }
 
-        Axial::new(q_out, r_out)
+    /// The hexagon at step `idx` of the line, which is `idx` away from the start.
+    pub(crate) fn nth_hex(&self, idx: u32) -> Axial {
+        let t = if self.steps == 0 {
+            0f64
+        } else {
+            idx as f64 / self.steps as f64
+        };
+        FractionalAxial::new(self.start.0 + self.delta.0 * t, self.start.1 + self.delta.1 * t).round()
     }
 }
 
+impl Iterator for LineToHex {
+    type Item = Axial;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.idx > self.steps {
+            return None;
+        }
+        let qr = self.nth_hex(self.idx);
+        self.idx += 1;
+        Some(qr)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = (self.steps + 1 - self.idx.min(self.steps + 1)) as usize;
+        (n, Some(n))
+    }
+}
+
+impl ExactSizeIterator for LineToHex {}
+
+/// Supercover Line Iterator
+/// Follows the line twice, nudged to both sides, and returns the hexagons of both.
+pub struct SupercoverToHex {
+    plus: LineToHex,
+    minus: LineToHex,
+    pending: Option<Axial>,
+    last: Option<Axial>,
+}
+
+impl SupercoverToHex {
+    fn new(from: Axial, to: Axial) -> Self {
+        SupercoverToHex {
+            plus: LineToHex::new(from, to, LINE_NUDGE),
+            minus: LineToHex::new(from, to, (-LINE_NUDGE.0, -LINE_NUDGE.1)),
+            pending: None,
+            last: None,
+        }
+    }
+}
+
+impl Iterator for SupercoverToHex {
+    type Item = Axial;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(qr) = self.pending.take() {
+            self.last = Some(qr);
+            return Some(qr);
+        }
+        let plus = self.plus.next()?;
+        let minus = self.minus.next()?;
+        if minus != plus && Some(minus) != self.last {
+            self.pending = Some(minus);
+        }
+        if Some(plus) == self.last {
+            return self.next();
+        }
+        self.last = Some(plus);
+        Some(plus)
+    }
+}
 
 /// Circle Iterator
 struct HexCircle {
@@ -179,6 +495,18 @@ impl HexCircle {
         }
     }
 
+    /// Number of hexagons not yet returned.
+    fn remaining(&self) -> usize {
+        if self.leg_idx > 5 {
+            0
+        } else if self.hex_radius == 0 {
+            1
+        } else {
+            let done = self.leg_idx as i32 * self.hex_radius + self.hex_idx;
+            (6 * self.hex_radius - done) as usize
+        }
+    }
+
     fn qr(&self) -> Option<(i32, i32)> {
         match self.leg_idx {
             0 => Some((-self.hex_idx,                     self.hex_radius)),
@@ -208,10 +536,23 @@ impl Iterator for HexCircle {
             self.hex_idx = 0;
             self.leg_idx += 1;
         }
-        qr.map(|v| Axial::from(v))
+        qr.map(Axial::from)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.remaining();
+        (n, Some(n))
     }
 }
 
+impl ExactSizeIterator for HexCircle {}
+
+/// Number of hexagons within `hex_radius`.
+fn hexes_within(hex_radius: u32) -> usize {
+    let n = hex_radius as usize;
+    3 * n * (n + 1) + 1
+}
+
 /// Circle around a given hexagon
 pub struct CircleAroundHex {
     circle: HexCircle,
@@ -224,8 +565,14 @@ impl Iterator for CircleAroundHex {
     fn next(&mut self) -> Option<Self::Item> {
         self.circle.next().map(|v| v + self.center)
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.circle.size_hint()
+    }
 }
 
+impl ExactSizeIterator for CircleAroundHex {}
+
 impl CircleAroundHex {
     pub fn new(center: Axial, radius: u32) -> Self {
         CircleAroundHex {
@@ -235,6 +582,92 @@ impl CircleAroundHex {
     }
 }
 
+/// Spiral around a given hexagon
+pub struct SpiralAroundHex {
+    circle: HexCircle,
+    center: Axial,
+    radius: u32,
+    hex_radius: u32,
+}
+
+impl SpiralAroundHex {
+    pub fn new(center: Axial, hex_radius: u32) -> Self {
+        SpiralAroundHex {
+            circle: HexCircle::new(0),
+            center,
+            radius: 0,
+            hex_radius,
+        }
+    }
+}
+
+impl Iterator for SpiralAroundHex {
+    type Item = Axial;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.circle.remaining() == 0 && self.radius < self.hex_radius {
+            self.radius += 1;
+            self.circle = HexCircle::new(self.radius);
+        }
+        self.circle.next().map(|v| v + self.center)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.circle.remaining() + hexes_within(self.hex_radius) - hexes_within(self.radius);
+        (n, Some(n))
+    }
+}
+
+impl ExactSizeIterator for SpiralAroundHex {}
+
+/// All hexagons within a distance of a given hexagon
+pub struct RangeAroundHex {
+    center: Axial,
+    hex_radius: i32,
+    q: i32,
+    r: i32,
+    remaining: usize,
+}
+
+impl RangeAroundHex {
+    pub fn new(center: Axial, hex_radius: u32) -> Self {
+        let n = hex_radius as i32;
+        RangeAroundHex {
+            center,
+            hex_radius: n,
+            q: -n,
+            r: 0,
+            remaining: hexes_within(hex_radius),
+        }
+    }
+}
+
+impl Iterator for RangeAroundHex {
+    type Item = Axial;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let n = self.hex_radius;
+        let qr = Axial::new(self.q, self.r);
+        if self.r < n.min(n - self.q) {
+            self.r += 1;
+        } else {
+            self.q += 1;
+            self.r = (-n).max(-n - self.q);
+        }
+        self.remaining -= 1;
+        Some(qr + self.center)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for RangeAroundHex {}
+
 
 #[cfg(test)]
 mod tests {
@@ -256,6 +689,13 @@ mod tests {
 
         let a: Axial = Axial::from((2.3f32, -13.6f32));
         assert_eq!((a.q, a.r), (2, -13));
+
+        let a: Axial = Axial::from((-2e30f32, 1e30f32));
+        assert_eq!((a.q, a.r), (i32::MIN, i32::MAX));
+        let a: Axial = Axial::from((f32::MAX, f32::MAX));
+        assert_eq!((a.q, a.r), (i32::MAX, i32::MAX));
+
+        assert_eq!(Axial::new(3u16, -2i8), Axial::new(3, -2));
     }
 
     #[test]
@@ -265,22 +705,99 @@ mod tests {
         assert_eq!(a+b, Axial::new(1, 2));
         assert_eq!(a-b, Axial::new(-1, -2));
         assert_eq!(2*b, Axial::new(2, 4));
-        assert_eq!(-0.5f32*b, (-0.5f32, -1.0f32));
+        assert_eq!(-0.5f32*b, FractionalAxial::new(-0.5f32, -1.0f32));
     }
 
     #[test]
     fn test_math() {
         let a = Axial::new(-1, 3);
         assert_eq!(a.length(), 3);
-        assert_eq!(Axial::default().length(), 0);
+        assert_eq!(Axial::<i32>::default().length(), 0);
         assert_eq!(a.distance_to(Axial::default()), 3);
         let b = Axial::new(2, -2);
         assert_eq!(b.distance_to(a), 5);
         assert_eq!(a.distance_to(b), 5);
         let c = Axial::new(4, 6);
         assert_eq!(c.distance_to(a), 8);
-        assert_eq!(a.lerp(c, 0.5f32), (1.5f32, 4.5f32));
+        assert_eq!(a.lerp(c, 0.5f32), FractionalAxial::new(1.5f32, 4.5f32));
         assert_eq!(Axial::point_on_line(a, c, 4f32), Axial::from((1.5f32, 4.5f32)));
+        assert_eq!(Axial::point_on_line(a, a, 4f32), a);
+    }
+
+    #[test]
+    fn test_widths() {
+        let a = Axial::<i16>::from_qr(300, -200);
+        assert_eq!(a.length(), 300u16);
+        assert_eq!(a.rotate_cw(), Axial::from_qr(200, 100));
+        assert_eq!(a.checked_mul(200), None);
+        assert_eq!(a.wrapping_mul(200), Axial::from_qr(60000i32 as i16, -40000i32 as i16));
+        assert_eq!(a.checked_add(Axial::from_qr(i16::MAX, 0)), None);
+        assert_eq!(a.checked_sub(a), Some(Axial::default()));
+        assert_eq!(Axial::from_qr(i16::MAX, 1).wrapping_add(Axial::from_qr(1, 1)), Axial::from_qr(i16::MIN, 2));
+
+        let wide = Axial::<i64>::from(a);
+        assert_eq!(wide, Axial::from_qr(300i64, -200i64));
+        assert_eq!(wide.try_cast::<i16>(), Some(a));
+        assert_eq!(Axial::<i64>::from_qr(1 << 40, 0).try_cast::<i32>(), None);
+        assert_eq!(Axial::from(Axial::<i8>::from_qr(-128, 127)), Axial::new(-128, 127));
+    }
+
+    #[test]
+    fn test_overflow() {
+        let max = Axial::new(i32::MAX, i32::MAX);
+        let min = Axial::new(i32::MIN, i32::MIN);
+        assert_eq!(max.checked_s(), None);
+        assert_eq!(Axial::new(i32::MAX, 0).checked_s(), Some(-i32::MAX));
+        assert_eq!(Axial::new(i32::MAX, i32::MIN).length(), i32::MAX as u32 + 1);
+        assert_eq!(Axial::new(i32::MAX, 0).distance_to(Axial::new(i32::MIN, 0)), u32::MAX);
+        assert_eq!(max.length(), u32::MAX - 1);
+        assert_eq!(min.length(), u32::MAX);
+        assert_eq!(max.distance_to(max), 0);
+
+        // the distance of the opposite corners needs the wide type
+        assert_eq!(max.wide_distance_to(min), 2 * (u32::MAX as u64));
+        assert_eq!(max.checked_distance_to(min), None);
+        assert_eq!(max.distance_to(min), u32::MAX);
+        assert_eq!(min.checked_length(), None);
+        assert_eq!(max.checked_length(), Some(u32::MAX - 1));
+        assert_eq!(max.checked_distance_to(Axial::new(-1, -1)), None);
+        assert_eq!(max.checked_distance_to(Axial::new(0, -1)), Some(u32::MAX));
+
+        let far = Axial::<i64>::from_qr(i64::MIN, i64::MAX);
+        assert_eq!(far.length(), 1u64 << 63);
+        assert_eq!(far.wide_distance_to(far.reflect_s()), u64::MAX as u128);
+        assert_eq!(far.distance_to(far.reflect_s()), u64::MAX);
+        assert_eq!(Axial::<i8>::from_qr(-100, 100).distance_to(Axial::from_qr(100, -100)), 200u8);
+        assert_eq!(Axial::<i8>::from_qr(i8::MIN, i8::MIN).wide_distance_to(Axial::from_qr(i8::MAX, i8::MAX)), 510u16);
+    }
+
+    #[test]
+    fn test_rotate_limits() {
+        // -q does not fit, but s and the rotation do
+        let edge = Axial::new(i32::MIN, 1);
+        assert_eq!(edge.checked_s(), Some(i32::MAX));
+        assert_eq!(Axial::new(i32::MAX, 1).checked_s(), Some(i32::MIN));
+        assert_eq!(edge.rotate_cw(), Axial::new(-1, i32::MIN + 1));
+        assert_eq!(edge.rotate_cw().rotate_ccw(), edge);
+
+        let min = Axial::new(i32::MIN, 0);
+        assert_eq!(min.checked_rotate_cw(), Some(Axial::new(0, i32::MIN)));
+        assert_eq!(min.checked_rotate_ccw(), None);
+        assert_eq!(min.wrapping_rotate_ccw(), Axial::new(i32::MIN, i32::MIN));
+        assert_eq!(min.checked_reflect_q(), None);
+        assert_eq!(min.checked_reflect_r(), None);
+        assert_eq!(min.wrapping_reflect_r(), min);
+        assert_eq!(Axial::new(1, i32::MIN).checked_rotate_cw(), None);
+        assert_eq!(Axial::new(1, i32::MIN).wrapping_rotate_cw(), Axial::new(i32::MIN, i32::MIN + 1));
+        assert_eq!(Axial::new(i32::MAX, i32::MAX).wrapping_s(), 2);
+
+        for qr in Axial::new(3, -7).range(3) {
+            assert_eq!(qr.checked_rotate_cw(), Some(qr.rotate_cw()));
+            assert_eq!(qr.wrapping_rotate_ccw(), qr.rotate_ccw());
+            assert_eq!(qr.checked_reflect_q(), Some(qr.reflect_q()));
+            assert_eq!(qr.wrapping_reflect_r(), qr.reflect_r());
+            assert_eq!(qr.rotate_cw().s(), -qr.q);
+        }
     }
 
     #[test]
@@ -317,4 +834,118 @@ mod tests {
         assert_eq!(ai.next(), Some(Axial::new(2, 0)));
         assert!(ai.next().is_none());
     }
-}
+
+    #[test]
+    fn test_spiral() {
+        let c = Axial::new(3, -1);
+        let mut ai = c.spiral(1);
+        assert_eq!(ai.len(), 7);
+        assert_eq!(ai.next(), Some(c));
+        assert_eq!(ai.len(), 6);
+        let ring: Vec<Axial> = ai.collect();
+        assert_eq!(ring, c.neighbours().collect::<Vec<Axial>>());
+
+        for n in 0..6u32 {
+            let ai = c.spiral(n);
+            let count = (3 * n * (n + 1) + 1) as usize;
+            assert_eq!(ai.len(), count);
+            let v: Vec<Axial> = ai.collect();
+            assert_eq!(v.len(), count);
+            for (i, a) in v.iter().enumerate() {
+                assert!(a.distance_to(c) <= n);
+                assert!(!v[..i].contains(a));
+                if i > 0 {
+                    assert!(a.distance_to(c) >= v[i - 1].distance_to(c));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_range() {
+        let c = Axial::new(-2, 5);
+        let mut ai = c.range(1);
+        assert_eq!(ai.len(), 7);
+        assert_eq!(ai.next(), Some(Axial::new(-3, 5)));
+        assert_eq!(ai.next(), Some(Axial::new(-3, 6)));
+        assert_eq!(ai.next(), Some(Axial::new(-2, 4)));
+        assert_eq!(ai.next(), Some(Axial::new(-2, 5)));
+        assert_eq!(ai.next(), Some(Axial::new(-2, 6)));
+        assert_eq!(ai.next(), Some(Axial::new(-1, 4)));
+        assert_eq!(ai.len(), 1);
+        assert_eq!(ai.next(), Some(Axial::new(-1, 5)));
+        assert!(ai.next().is_none());
+        assert_eq!(ai.len(), 0);
+
+        for n in 0..6u32 {
+            let count = (3 * n * (n + 1) + 1) as usize;
+            assert_eq!(c.range(n).len(), count);
+            let v: Vec<Axial> = c.range(n).collect();
+            assert_eq!(v.len(), count);
+            let mut sorted = v.clone();
+            sorted.sort_by_key(|a| a.to_tuple());
+            assert_eq!(v, sorted);
+            let mut spiral: Vec<Axial> = c.spiral(n).collect();
+            spiral.sort_by_key(|a| a.to_tuple());
+            assert_eq!(v, spiral);
+        }
+    }
+
+    #[test]
+    fn test_circle_len() {
+        assert_eq!(HexCircle::new(0).len(), 1);
+        let mut ai = Axial::default().circle(3);
+        assert_eq!(ai.len(), 18);
+        ai.next();
+        ai.next();
+        ai.next();
+        assert_eq!(ai.len(), 15);
+        assert_eq!(ai.count(), 15);
+    }
+
+    #[test]
+    fn test_line() {
+        let a = Axial::new(2, -1);
+        let v: Vec<Axial> = a.line_to(a).collect();
+        assert_eq!(v, vec![a]);
+
+        let v: Vec<Axial> = Axial::default().line_to(Axial::new(3, 0)).collect();
+        assert_eq!(v, vec![Axial::new(0, 0), Axial::new(1, 0), Axial::new(2, 0), Axial::new(3, 0)]);
+
+        // along the edge between (1, 0) and (0, 1)
+        let v: Vec<Axial> = Axial::default().line_to(Axial::new(1, 1)).collect();
+        assert_eq!(v, vec![Axial::new(0, 0), Axial::new(0, 1), Axial::new(1, 1)]);
+
+        for b in Axial::new(-3, 1).range(6) {
+            let ai = a.line_to(b);
+            assert_eq!(ai.len(), a.distance_to(b) as usize + 1);
+            let v: Vec<Axial> = ai.collect();
+            assert_eq!(v.first(), Some(&a));
+            assert_eq!(v.last(), Some(&b));
+            for w in v.windows(2) {
+                assert_eq!(w[0].distance_to(w[1]), 1);
+            }
+        }
+
+        let far = Axial::new(100_000, -250_000);
+        let v: Vec<Axial> = far.line_to(far + Axial::new(4, 4)).collect();
+        assert_eq!(v.len(), 9);
+        assert_eq!(v[1], far + Axial::new(0, 1));
+    }
+
+    #[test]
+    fn test_supercover() {
+        let v: Vec<Axial> = Axial::default().supercover_to(Axial::new(1, 1)).collect();
+        assert_eq!(v, vec![Axial::new(0, 0), Axial::new(0, 1), Axial::new(1, 0), Axial::new(1, 1)]);
+
+        let v: Vec<Axial> = Axial::default().supercover_to(Axial::new(-2, -2)).collect();
+        assert_eq!(v.len(), 7);
+
+        let v: Vec<Axial> = Axial::default().supercover_to(Axial::new(3, -1)).collect();
+        assert_eq!(v, Axial::default().line_to(Axial::new(3, -1)).collect::<Vec<Axial>>());
+
+        let a = Axial::new(1, 1);
+        let v: Vec<Axial> = a.supercover_to(a).collect();
+        assert_eq!(v, vec![a]);
+    }
+}
\ No newline at end of file
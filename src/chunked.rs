@@ -0,0 +1,319 @@
+//! Unbounded storage, split into chunks which are only allocated when used.
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::axial::Axial;
+use super::map::{HexMap, MapShape};
+
+/// Axial coordinates of `(q, r)`, if both fit into `i32`.
+fn narrow((q, r): (i64, i64)) -> Option<Axial> {
+    Some(Axial::new(i32::try_from(q).ok()?, i32::try_from(r).ok()?))
+}
+
+/// Axial coordinates of `(q, r)`, wrapping around at the limits of `i32` like `Axial::wrapping_add`.
+fn wrap((q, r): (i64, i64)) -> Axial {
+    Axial::new(q as i32, r as i32)
+}
+
+/// How hexagons are grouped into chunks.
+/// The chunks at the limits of the `i32` range reach beyond it, only their hexagons inside are used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ChunkShape {
+    /// Hexagons of hexagons with the given radius.
+    /// The chunk `(a, b)` is centered at `a * (2n+1, -n) + b * (n, n+1)`.
+    Hexagon { radius: u32 },
+    /// Parallelograms of `size` times `size` hexagons, a size of 0 is treated like 1.
+    /// The chunk `(a, b)` starts at `(a * size, b * size)`.
+    Parallelogram { size: u32 },
+}
+
+impl ChunkShape {
+    /// The lattice vectors between the origins of neighbouring chunks.
+    fn lattice(&self) -> ((i64, i64), (i64, i64)) {
+        match *self {
+            ChunkShape::Hexagon { radius } => {
+                let n = radius as i64;
+                ((2 * n + 1, -n), (n, n + 1))
+            },
+            ChunkShape::Parallelogram { size } => {
+                let n = size.max(1) as i64;
+                ((n, 0), (0, n))
+            },
+        }
+    }
+
+    /// The shape of a single chunk relative to its origin.
+    pub fn map_shape(&self) -> MapShape {
+        match *self {
+            ChunkShape::Hexagon { radius } => MapShape::Hexagon { radius },
+            ChunkShape::Parallelogram { size } => {
+                let n = size.max(1) as i32;
+                MapShape::Parallelogram { q: (0, n - 1), r: (0, n - 1) }
+            },
+        }
+    }
+
+    /// The origin of `chunk` without overflow.
+    fn wide_origin(&self, chunk: Axial) -> (i64, i64) {
+        let ((q1, r1), (q2, r2)) = self.lattice();
+        let (a, b) = chunk.to_tuple();
+        let (a, b) = (a as i64, b as i64);
+        (a * q1 + b * q2, a * r1 + b * r2)
+    }
+
+    /// The origin of `chunk` as Axial coordinates: the center of a hexagon or
+    /// the corner of a parallelogram.
+    /// `None` if the origin is outside of the `i32` range, which happens for chunks at the limits.
+    pub fn origin(&self, chunk: Axial) -> Option<Axial> {
+        narrow(self.wide_origin(chunk))
+    }
+
+    /// The chunk containing `qr`.
+    pub fn chunk_of(&self, qr: Axial) -> Axial {
+        let (q, r) = qr.to_tuple();
+        match *self {
+            ChunkShape::Parallelogram { size } => {
+                let n = size.max(1) as i32;
+                Axial::new(q.div_euclid(n), r.div_euclid(n))
+            },
+            ChunkShape::Hexagon { radius } => {
+                // solve qr = a * v1 + b * v2, the chunk is one of the lattice points around the solution
+                let ((q1, r1), (q2, r2)) = self.lattice();
+                let det = (q1 * r2 - q2 * r1) as f64;
+                let (qf, rf) = (q as f64, r as f64);
+                let af = (qf * r2 as f64 - rf * q2 as f64) / det;
+                let bf = (rf * q1 as f64 - qf * r1 as f64) / det;
+                let (a0, b0) = (af.floor() as i32, bf.floor() as i32);
+                (a0 - 1..=a0 + 2)
+                    .flat_map(|a| (b0 - 1..=b0 + 2).map(move |b| Axial::new(a, b)))
+                    .find(|chunk| {
+                        let (dq, dr) = self.wide_local(*chunk, qr);
+                        dq.abs().max(dr.abs()).max((dq + dr).abs()) <= radius as i64
+                    })
+                    .unwrap_or_else(|| unreachable!("no chunk contains {:?}", qr))
+            },
+        }
+    }
+
+    /// `qr` relative to the origin of `chunk` without overflow.
+    fn wide_local(&self, chunk: Axial, qr: Axial) -> (i64, i64) {
+        let (q, r) = qr.to_tuple();
+        let (oq, or) = self.wide_origin(chunk);
+        (q as i64 - oq, r as i64 - or)
+    }
+
+    /// `qr` relative to the origin of `chunk`, which has to contain `qr`.
+    fn local(&self, chunk: Axial, qr: Axial) -> Axial {
+        narrow(self.wide_local(chunk, qr))
+            .unwrap_or_else(|| panic!("{:?} is too far from the origin of chunk {:?}", qr, chunk))
+    }
+
+    /// `qr` relative to the origin of its chunk.
+    pub fn local_of(&self, qr: Axial) -> Axial {
+        self.local(self.chunk_of(qr), qr)
+    }
+}
+
+/// Values of type `T` for an unbounded set of hexagons, allocated chunk by chunk.
+#[derive(Debug, Clone)]
+pub struct ChunkedHexMap<T> {
+    shape: ChunkShape,
+    chunks: HashMap<Axial, HexMap<T>>,
+}
+
+impl<T> ChunkedHexMap<T> {
+    pub fn new(shape: ChunkShape) -> Self {
+        ChunkedHexMap {
+            shape,
+            chunks: HashMap::new(),
+        }
+    }
+
+    pub fn shape(&self) -> ChunkShape {
+        self.shape
+    }
+
+    pub fn is_loaded(&self, chunk: Axial) -> bool {
+        self.chunks.contains_key(&chunk)
+    }
+
+    /// Number of loaded chunks.
+    pub fn loaded(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// The value of `qr`, if its chunk is loaded.
+    pub fn get(&self, qr: Axial) -> Option<&T> {
+        let chunk = self.shape.chunk_of(qr);
+        self.chunks.get(&chunk)?.get(self.shape.local(chunk, qr))
+    }
+
+    /// The mutable value of `qr`, if its chunk is loaded.
+    pub fn get_mut(&mut self, qr: Axial) -> Option<&mut T> {
+        let chunk = self.shape.chunk_of(qr);
+        let local = self.shape.local(chunk, qr);
+        self.chunks.get_mut(&chunk)?.get_mut(local)
+    }
+
+    /// The chunk with coordinates `chunk`, created with `f(qr)` for every hexagon if it is not loaded.
+    /// A chunk at the limits of the `i32` range also calls `f` for its hexagons beyond,
+    /// with wrapped coordinates. Their values are never returned by `get` or `iter`.
+    pub fn chunk_or_insert_with(&mut self, chunk: Axial, mut f: impl FnMut(Axial) -> T) -> &mut HexMap<T> {
+        let (oq, or) = self.shape.wide_origin(chunk);
+        let shape = self.shape.map_shape();
+        self.chunks.entry(chunk)
+            .or_insert_with(|| HexMap::from_fn(shape, |local| {
+                let (q, r) = local.to_tuple();
+                f(wrap((oq + q as i64, or + r as i64)))
+            }))
+    }
+
+    /// The mutable value of `qr`, loading its chunk with `f(qr)` for every hexagon if needed.
+    pub fn get_or_insert_with(&mut self, qr: Axial, f: impl FnMut(Axial) -> T) -> &mut T {
+        let chunk = self.shape.chunk_of(qr);
+        let local = self.shape.local(chunk, qr);
+        &mut self.chunk_or_insert_with(chunk, f)[local]
+    }
+
+    /// The loaded chunks with their coordinates, in no particular order.
+    pub fn chunks(&self) -> impl Iterator<Item = (Axial, &HexMap<T>)> {
+        self.chunks.iter().map(|(chunk, map)| (*chunk, map))
+    }
+
+    /// All hexagons of the loaded chunks with their values.
+    pub fn iter(&self) -> impl Iterator<Item = (Axial, &T)> {
+        self.chunks.iter().flat_map(move |(chunk, map)| {
+            let (oq, or) = self.shape.wide_origin(*chunk);
+            map.iter().filter_map(move |(local, v)| {
+                let (q, r) = local.to_tuple();
+                Some((narrow((oq + q as i64, or + r as i64))?, v))
+            })
+        })
+    }
+
+    /// Removes a chunk and returns its values.
+    pub fn evict(&mut self, chunk: Axial) -> Option<HexMap<T>> {
+        self.chunks.remove(&chunk)
+    }
+
+    /// Keeps only the chunks for which `f(chunk)` is true.
+    pub fn retain_chunks(&mut self, mut f: impl FnMut(Axial) -> bool) {
+        self.chunks.retain(|chunk, _| f(*chunk));
+    }
+}
+
+impl<T: Default> ChunkedHexMap<T> {
+    /// The mutable value of `qr`, loading its chunk with default values if needed.
+    pub fn get_or_default(&mut self, qr: Axial) -> &mut T {
+        self.get_or_insert_with(qr, |_| T::default())
+    }
+
+    /// Sets the value of `qr`, loading its chunk with default values if needed.
+    pub fn insert(&mut self, qr: Axial, value: T) {
+        *self.get_or_default(qr) = value;
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_of() {
+        for shape in [ChunkShape::Hexagon { radius: 0 }, ChunkShape::Hexagon { radius: 3 }, ChunkShape::Parallelogram { size: 4 }] {
+            let map_shape = shape.map_shape();
+            let mut counts: HashMap<Axial, usize> = HashMap::new();
+            for qr in Axial::new(-5, 3).range(30) {
+                let chunk = shape.chunk_of(qr);
+                let local = shape.local_of(qr);
+                assert!(map_shape.contains(local), "{:?} {:?}", shape, qr);
+                assert_eq!(shape.origin(chunk).unwrap() + local, qr);
+                *counts.entry(chunk).or_default() += 1;
+            }
+            // chunks completely inside the scanned range are full
+            let full = map_shape.hexes().count();
+            let inner = counts.keys()
+                .filter(|chunk| shape.origin(**chunk).unwrap().distance_to(Axial::new(-5, 3)) < 20)
+                .count();
+            assert!(inner > 0);
+            for (chunk, count) in counts {
+                if shape.origin(chunk).unwrap().distance_to(Axial::new(-5, 3)) < 20 {
+                    assert_eq!(count, full, "{:?} {:?}", shape, chunk);
+                }
+            }
+        }
+        let shape = ChunkShape::Parallelogram { size: 4 };
+        assert_eq!(shape.chunk_of(Axial::new(-1, -4)), Axial::new(-1, -1));
+        assert_eq!(shape.local_of(Axial::new(-1, -4)), Axial::new(3, 0));
+        let shape = ChunkShape::Hexagon { radius: 2 };
+        assert_eq!(shape.origin(Axial::new(-1, 1)), Some(Axial::new(-3, 5)));
+        assert_eq!(shape.chunk_of(Axial::new(-3, 6)), Axial::new(-1, 1));
+    }
+
+    #[test]
+    fn test_degenerate() {
+        let mut map: ChunkedHexMap<i32> = ChunkedHexMap::new(ChunkShape::Parallelogram { size: 0 });
+        map.insert(Axial::new(-3, 7), 1);
+        assert_eq!(map.get(Axial::new(-3, 7)), Some(&1));
+        assert_eq!(map.shape().map_shape().hexes().count(), 1);
+
+        // hexagons at the edges of the range find their chunk, even if its center is outside
+        let shape = ChunkShape::Hexagon { radius: 5 };
+        for qr in [Axial::new(i32::MAX, 0), Axial::new(i32::MIN, i32::MAX), Axial::new(0, i32::MIN)] {
+            let chunk = shape.chunk_of(qr);
+            assert!(shape.map_shape().contains(shape.local_of(qr)), "{:?} {:?}", qr, chunk);
+        }
+    }
+
+    #[test]
+    fn test_limits() {
+        assert_eq!(ChunkShape::Parallelogram { size: 1000 }.origin(Axial::new(i32::MAX, 0)), None);
+
+        for shape in [ChunkShape::Hexagon { radius: 3 }, ChunkShape::Parallelogram { size: 7 }] {
+            let mut map: ChunkedHexMap<Axial> = ChunkedHexMap::new(shape);
+            let corners = [Axial::new(i32::MAX, i32::MIN), Axial::new(i32::MIN, i32::MAX),
+                Axial::new(i32::MAX, i32::MAX), Axial::new(i32::MIN, i32::MIN)];
+            for qr in corners {
+                assert_eq!(*map.get_or_insert_with(qr, |qr| qr), qr, "{:?}", shape);
+            }
+            map.insert(Axial::new(i32::MAX, i32::MIN), Axial::default());
+            assert_eq!(map.get(Axial::new(i32::MAX, i32::MIN)), Some(&Axial::default()));
+            // the wrapped hexagons of the edge chunks are not visible
+            for (qr, v) in map.iter() {
+                assert!(*v == qr || qr == Axial::new(i32::MAX, i32::MIN), "{:?} {:?} {:?}", shape, qr, v);
+                assert_eq!(map.get(qr), Some(v));
+            }
+            assert!(map.iter().any(|(qr, _)| qr == Axial::new(i32::MAX, i32::MIN)));
+            assert!(map.iter().count() < map.chunks().map(|(_, c)| c.len()).sum::<usize>());
+        }
+    }
+
+    #[test]
+    fn test_map() {
+        let mut map: ChunkedHexMap<i32> = ChunkedHexMap::new(ChunkShape::Hexagon { radius: 2 });
+        assert_eq!(map.get(Axial::default()), None);
+        map.insert(Axial::new(-10, 4), 5);
+        assert_eq!(map.loaded(), 1);
+        assert_eq!(map.get(Axial::new(-10, 4)), Some(&5));
+        assert_eq!(map.iter().count(), 19);
+
+        *map.get_or_insert_with(Axial::new(30, -7), |qr| qr.to_tuple().0) += 1;
+        assert_eq!(map.get(Axial::new(30, -7)), Some(&31));
+        let chunk = map.shape().chunk_of(Axial::new(30, -7));
+        assert!(map.is_loaded(chunk));
+        for (qr, v) in map.iter().filter(|(qr, _)| map.shape().chunk_of(*qr) == chunk) {
+            if qr != Axial::new(30, -7) {
+                assert_eq!(*v, qr.to_tuple().0);
+            }
+        }
+        assert_eq!(map.chunks().count(), 2);
+
+        let evicted = map.evict(chunk).unwrap();
+        assert_eq!(evicted.len(), 19);
+        assert_eq!(map.get(Axial::new(30, -7)), None);
+        map.retain_chunks(|_| false);
+        assert_eq!(map.loaded(), 0);
+    }
+}
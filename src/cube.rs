@@ -0,0 +1,372 @@
+//! Cube coordinates and fractional hexagons.
+//! Following the great page https://www.redblobgames.com/grids/hexagons/
+use std::ops::{Add, Mul, Neg, Sub};
+
+use super::axial::Axial;
+use super::float::Float;
+
+/// Cube coordinates of a hexagon, always with `q + r + s == 0`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cube {
+    q: i32,
+    r: i32,
+    s: i32,
+}
+
+/// A point between hexagon centers in axial coordinates.
+/// Use `f64` to keep the rounding exact far away from the origin.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct FractionalAxial<F = f32> {
+    q: F,
+    r: F,
+}
+
+/// A point between hexagon centers in cube coordinates, always with `q + r + s == 0`.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct FractionalCube<F = f32> {
+    q: F,
+    r: F,
+    s: F,
+}
+
+impl Cube {
+    pub fn new(q: i32, r: i32) -> Self {
+        Cube { q, r, s: -q - r }
+    }
+
+    /// Creates a cube if `q + r + s == 0`.
+    pub fn try_new(q: i32, r: i32, s: i32) -> Option<Self> {
+        if q as i64 + r as i64 + s as i64 == 0 {
+            Some(Cube { q, r, s })
+        } else {
+            None
+        }
+    }
+
+    pub fn q(&self) -> i32 { self.q }
+
+    pub fn r(&self) -> i32 { self.r }
+
+    pub fn s(&self) -> i32 { self.s }
+
+    pub fn length(&self) -> u32 {
+        Axial::from(*self).length()
+    }
+
+    pub fn distance_to(&self, other: Self) -> u32 {
+        (*self - other).length()
+    }
+
+    pub fn lerp(&self, other: Self, t: f32) -> FractionalCube {
+        FractionalCube::from(*self).lerp(other.into(), t)
+    }
+}
+
+impl<F: Float> FractionalAxial<F> {
+    pub fn new(q: F, r: F) -> Self {
+        FractionalAxial { q, r }
+    }
+
+    pub fn q(&self) -> F { self.q }
+
+    pub fn r(&self) -> F { self.r }
+
+    pub fn s(&self) -> F { -self.q - self.r }
+
+    pub fn to_tuple(&self) -> (F, F) {
+        (self.q, self.r)
+    }
+
+    pub fn lerp(&self, other: Self, t: F) -> Self {
+        *self * (F::ONE - t) + other * t
+    }
+
+    /// The hexagon containing this point, saturating at the limits of `i32`.
+    pub fn round(&self) -> Axial {
+        let c = FractionalCube::from(*self);
+        let (q, r) = round_cube(c.q, c.r, c.s, F::round);
+        Axial::new(q.to_i32(), r.to_i32())
+    }
+}
+
+impl FractionalAxial<f32> {
+    pub fn to_f32s(&self) -> (f32, f32) {
+        (self.q, self.r)
+    }
+}
+
+impl<F: Float> FractionalCube<F> {
+    pub fn new(q: F, r: F) -> Self {
+        FractionalCube { q, r, s: -q - r }
+    }
+
+    pub fn q(&self) -> F { self.q }
+
+    pub fn r(&self) -> F { self.r }
+
+    pub fn s(&self) -> F { self.s }
+
+    pub fn lerp(&self, other: Self, t: F) -> Self {
+        FractionalCube::from(FractionalAxial::from(*self).lerp(other.into(), t))
+    }
+
+    /// The hexagon containing this point.
+    /// The coordinate which is furthest from its rounded value is computed from the other two.
+    /// Far away from the origin the cube is clamped, so that all three coordinates fit into `i32`.
+    pub fn round(&self) -> Cube {
+        // stay in floats until the end, so huge values saturate instead of overflowing
+        let (q, r) = round_cube(self.q, self.r, self.s, F::round);
+        clamped_cube(q.to_i32(), r.to_i32())
+    }
+}
+
+/// The cube of `q` and `r`, moved along s until `s` fits into `i32` as well.
+fn clamped_cube(q: i32, r: i32) -> Cube {
+    let (q, r) = (q as i64, r as i64);
+    // s fits if q + r is between i32::MIN + 1 and i32::MAX + 1
+    let sum = q + r;
+    let excess = sum - sum.clamp(i32::MIN as i64 + 1, i32::MAX as i64 + 1);
+    // q and r move towards 0 by half of the excess each, so they keep fitting
+    let (q, r) = (q - excess / 2, r - (excess - excess / 2));
+    Cube { q: q as i32, r: r as i32, s: (-q - r) as i32 }
+}
+
+/// Cube rounding of `(q, r, s)`, rounding the single coordinates with `round`.
+/// Returns the whole q and r, still as floats.
+#[inline]
+pub(crate) fn round_cube<F: Float>(q_f: F, r_f: F, s_f: F, round: impl Fn(F) -> F) -> (F, F) {
+    let q = round(q_f);
+    let r = round(r_f);
+    let s = round(s_f);
+
+    let q_diff = (q - q_f).abs();
+    let r_diff = (r - r_f).abs();
+    let s_diff = (s - s_f).abs();
+
+    let calc_q = q_diff > r_diff && q_diff > s_diff;
+    let calc_r = !calc_q && r_diff > s_diff;
+
+    let q_out = if calc_q { -r - s } else { q };
+    let r_out = if calc_r { -q - s } else { r };
+    (q_out, r_out)
+}
+
+impl From<Axial> for Cube {
+    fn from(qr: Axial) -> Self {
+        let (q, r) = qr.to_tuple();
+        Cube::new(q, r)
+    }
+}
+
+impl From<Cube> for Axial {
+    fn from(c: Cube) -> Self {
+        Axial::new(c.q, c.r)
+    }
+}
+
+impl<F: Float> From<Axial> for FractionalAxial<F> {
+    fn from(qr: Axial) -> Self {
+        let (q, r) = qr.to_tuple();
+        FractionalAxial::new(F::from_i32(q), F::from_i32(r))
+    }
+}
+
+impl<F: Float> From<Cube> for FractionalCube<F> {
+    fn from(c: Cube) -> Self {
+        FractionalCube::new(F::from_i32(c.q), F::from_i32(c.r))
+    }
+}
+
+impl<F: Float> From<FractionalAxial<F>> for FractionalCube<F> {
+    fn from(f: FractionalAxial<F>) -> Self {
+        FractionalCube::new(f.q, f.r)
+    }
+}
+
+impl<F: Float> From<FractionalCube<F>> for FractionalAxial<F> {
+    fn from(f: FractionalCube<F>) -> Self {
+        FractionalAxial::new(f.q, f.r)
+    }
+}
+
+impl<F: Float> From<(F, F)> for FractionalAxial<F> {
+    fn from((q, r): (F, F)) -> Self {
+        FractionalAxial::new(q, r)
+    }
+}
+
+impl<F: Float> From<FractionalAxial<F>> for (F, F) {
+    fn from(f: FractionalAxial<F>) -> Self {
+        f.to_tuple()
+    }
+}
+
+impl Add for Cube {
+    type Output = Cube;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Cube::new(self.q + rhs.q, self.r + rhs.r)
+    }
+}
+
+impl Sub for Cube {
+    type Output = Cube;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Cube::new(self.q - rhs.q, self.r - rhs.r)
+    }
+}
+
+impl Neg for Cube {
+    type Output = Cube;
+
+    fn neg(self) -> Self::Output {
+        Cube::new(-self.q, -self.r)
+    }
+}
+
+impl Mul<i32> for Cube {
+    type Output = Cube;
+
+    fn mul(self, rhs: i32) -> Self::Output {
+        Cube::new(self.q * rhs, self.r * rhs)
+    }
+}
+
+impl<F: Float> Add for FractionalAxial<F> {
+    type Output = FractionalAxial<F>;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        FractionalAxial::new(self.q + rhs.q, self.r + rhs.r)
+    }
+}
+
+impl<F: Float> Sub for FractionalAxial<F> {
+    type Output = FractionalAxial<F>;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        FractionalAxial::new(self.q - rhs.q, self.r - rhs.r)
+    }
+}
+
+impl<F: Float> Neg for FractionalAxial<F> {
+    type Output = FractionalAxial<F>;
+
+    fn neg(self) -> Self::Output {
+        FractionalAxial::new(-self.q, -self.r)
+    }
+}
+
+impl<F: Float> Mul<F> for FractionalAxial<F> {
+    type Output = FractionalAxial<F>;
+
+    fn mul(self, rhs: F) -> Self::Output {
+        FractionalAxial::new(self.q * rhs, self.r * rhs)
+    }
+}
+
+impl<F: Float> Add for FractionalCube<F> {
+    type Output = FractionalCube<F>;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        FractionalCube::new(self.q + rhs.q, self.r + rhs.r)
+    }
+}
+
+impl<F: Float> Sub for FractionalCube<F> {
+    type Output = FractionalCube<F>;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        FractionalCube::new(self.q - rhs.q, self.r - rhs.r)
+    }
+}
+
+impl<F: Float> Neg for FractionalCube<F> {
+    type Output = FractionalCube<F>;
+
+    fn neg(self) -> Self::Output {
+        FractionalCube::new(-self.q, -self.r)
+    }
+}
+
+impl<F: Float> Mul<F> for FractionalCube<F> {
+    type Output = FractionalCube<F>;
+
+    fn mul(self, rhs: F) -> Self::Output {
+        FractionalCube::new(self.q * rhs, self.r * rhs)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cube() {
+        let c = Cube::from(Axial::new(2, -5));
+        assert_eq!((c.q(), c.r(), c.s()), (2, -5, 3));
+        assert_eq!(Axial::from(c), Axial::new(2, -5));
+        assert_eq!(Cube::try_new(1, 2, -3), Some(Cube::new(1, 2)));
+        assert_eq!(Cube::try_new(1, 2, 3), None);
+        assert_eq!(c + Cube::new(-2, 5), Cube::default());
+        assert_eq!(c - c, Cube::default());
+        assert_eq!(-c, Cube::new(-2, 5));
+        assert_eq!(c * 2, Cube::new(4, -10));
+        assert_eq!(c.length(), 5);
+        assert_eq!(c.distance_to(Cube::new(0, 1)), 6);
+    }
+
+    #[test]
+    fn test_fractional() {
+        let f = FractionalAxial::new(0.25f32, 1.125f32);
+        assert_eq!(f.s(), -1.375f32);
+        let c = FractionalCube::from(f);
+        assert_eq!(c.q() + c.r() + c.s(), 0f32);
+        assert_eq!(f.round(), Axial::new(0, 1));
+        assert_eq!(c.round(), Cube::new(0, 1));
+        assert_eq!(f.round(), Axial::from((0.25f32, 1.125f32)));
+        assert_eq!(f + f, FractionalAxial::new(0.5f32, 2.25f32));
+        assert_eq!(f - f, FractionalAxial::default());
+        assert_eq!(-f * 0.5f32, FractionalAxial::new(-0.125f32, -0.5625f32));
+        assert_eq!((c - c).s(), 0f32);
+
+        let a = Cube::new(-1, 3);
+        let b = Cube::new(4, 6);
+        assert_eq!(a.lerp(b, 0.5f32), FractionalCube::new(1.5f32, 4.5f32));
+        assert_eq!(a.lerp(b, 0.5f32).round(), Cube::from(Axial::from((1.5f32, 4.5f32))));
+        let fa = FractionalAxial::from(Axial::from(a));
+        assert_eq!(fa.lerp(Axial::from(b).into(), 1f32), FractionalAxial::new(4f32, 6f32));
+        assert_eq!(<(f32, f32)>::from(fa), (-1f32, 3f32));
+    }
+
+    #[test]
+    fn test_fractional_f64() {
+        // f32 has no fractional digits left at 3 * 10^7, the nearest hexagon is lost
+        let (q, r) = (30_000_001.2, -30_000_001.1);
+        assert_eq!(FractionalAxial::new(q, r).round(), Axial::new(30_000_001, -30_000_001));
+        assert_ne!(FractionalAxial::new(q as f32, r as f32).round(), Axial::new(30_000_001, -30_000_001));
+        let c = FractionalCube::from(FractionalAxial::new(q, r));
+        assert_eq!(c.round(), Cube::new(30_000_001, -30_000_001));
+    }
+
+    #[test]
+    fn test_round_limits() {
+        let values = [0.0, 1e9, -1e9, 2.2e9, -2.2e9, 3e9, -3e9, 1e300, -1e300, f64::INFINITY, f64::NEG_INFINITY, f64::NAN];
+        for q in values {
+            for r in values {
+                let c = FractionalCube::new(q, r).round();
+                assert_eq!(c.q() as i64 + c.r() as i64 + c.s() as i64, 0, "{} {}", q, r);
+                assert_eq!(Cube::try_new(c.q(), c.r(), c.s()), Some(c));
+            }
+        }
+        let c = FractionalCube::new(3e9, 3e9).round();
+        assert_eq!(c.s(), i32::MIN);
+        assert_eq!((c.q(), c.r()), (i32::MAX / 2 + 1, i32::MAX / 2 + 1));
+        let c = FractionalCube::new(-3e9, -3e9).round();
+        assert_eq!((c.q(), c.r(), c.s()), (i32::MIN / 2, i32::MIN / 2 + 1, i32::MAX));
+        // inside the range nothing is clamped
+        assert_eq!(FractionalCube::new(2e9f64, -1e9).round(), Cube::new(2_000_000_000, -1_000_000_000));
+        assert_eq!(FractionalAxial::new(3e9f64, 3e9).round(), Axial::new(i32::MAX, i32::MAX));
+    }
+}
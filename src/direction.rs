@@ -0,0 +1,229 @@
+//! Directions to the neighbours of a hexagon.
+//!
+//! The variants are named after the cube coordinates: `QS` increases q and decreases s.
+//! The compass names depend on the orientation of the hexagons, see `name`.
+use super::axial::Axial;
+use super::HexTop;
+
+/// Direction through one of the six edges of a hexagon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HexDirection {
+    QS,
+    RS,
+    RQ,
+    SQ,
+    SR,
+    QR,
+}
+
+/// Direction through one of the six corners of a hexagon, named after
+/// the cube coordinate which changes by two.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HexDiagonal {
+    PlusQ,
+    MinusS,
+    PlusR,
+    MinusQ,
+    PlusS,
+    MinusR,
+}
+
+const DIRECTIONS: [HexDirection; 6] = [
+    HexDirection::QS,
+    HexDirection::RS,
+    HexDirection::RQ,
+    HexDirection::SQ,
+    HexDirection::SR,
+    HexDirection::QR,
+];
+
+const DIAGONALS: [HexDiagonal; 6] = [
+    HexDiagonal::PlusQ,
+    HexDiagonal::MinusS,
+    HexDiagonal::PlusR,
+    HexDiagonal::MinusQ,
+    HexDiagonal::PlusS,
+    HexDiagonal::MinusR,
+];
+
+impl HexDirection {
+    /// All directions in clockwise order.
+    pub fn all() -> [HexDirection; 6] {
+        DIRECTIONS
+    }
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+
+    pub fn offset(&self) -> Axial {
+        match self {
+            HexDirection::QS => Axial::new( 1,  0),
+            HexDirection::RS => Axial::new( 0,  1),
+            HexDirection::RQ => Axial::new(-1,  1),
+            HexDirection::SQ => Axial::new(-1,  0),
+            HexDirection::SR => Axial::new( 0, -1),
+            HexDirection::QR => Axial::new( 1, -1),
+        }
+    }
+
+    pub fn opposite(&self) -> Self {
+        self.rotate(3)
+    }
+
+    /// Rotate by `steps` times 60°, clockwise for positive `steps`.
+    pub fn rotate(&self, steps: i32) -> Self {
+        DIRECTIONS[(self.index() as i32 + steps).rem_euclid(6) as usize]
+    }
+
+    pub fn rotate_cw(&self) -> Self {
+        self.rotate(1)
+    }
+
+    pub fn rotate_ccw(&self) -> Self {
+        self.rotate(-1)
+    }
+
+    /// Compass name with y pointing down.
+    pub fn name(&self, top: HexTop) -> &'static str {
+        let names = match top {
+            HexTop::FLAT => ["SE", "S", "SW", "NW", "N", "NE"],
+            HexTop::POINTY => ["E", "SE", "SW", "W", "NW", "NE"],
+        };
+        names[self.index()]
+    }
+
+    /// The two corners at the ends of the edge in this direction, in clockwise order.
+    pub fn corners(&self) -> (HexDiagonal, HexDiagonal) {
+        (DIAGONALS[self.index()], DIAGONALS[(self.index() + 1) % 6])
+    }
+
+    /// Direction in which `to` lies from `from`, if they are neighbours.
+    pub fn between(from: Axial, to: Axial) -> Option<Self> {
+        let d = to - from;
+        DIRECTIONS.iter().copied().find(|dir| dir.offset() == d)
+    }
+}
+
+impl HexDiagonal {
+    /// All diagonals in clockwise order.
+    pub fn all() -> [HexDiagonal; 6] {
+        DIAGONALS
+    }
+
+    pub(crate) fn index(&self) -> usize {
+        *self as usize
+    }
+
+    pub fn offset(&self) -> Axial {
+        match self {
+            HexDiagonal::PlusQ => Axial::new( 2, -1),
+            HexDiagonal::MinusS => Axial::new( 1,  1),
+            HexDiagonal::PlusR => Axial::new(-1,  2),
+            HexDiagonal::MinusQ => Axial::new(-2,  1),
+            HexDiagonal::PlusS => Axial::new(-1, -1),
+            HexDiagonal::MinusR => Axial::new( 1, -2),
+        }
+    }
+
+    pub fn opposite(&self) -> Self {
+        self.rotate(3)
+    }
+
+    /// Rotate by `steps` times 60°, clockwise for positive `steps`.
+    pub fn rotate(&self, steps: i32) -> Self {
+        DIAGONALS[(self.index() as i32 + steps).rem_euclid(6) as usize]
+    }
+
+    pub fn rotate_cw(&self) -> Self {
+        self.rotate(1)
+    }
+
+    pub fn rotate_ccw(&self) -> Self {
+        self.rotate(-1)
+    }
+
+    /// Compass name with y pointing down.
+    pub fn name(&self, top: HexTop) -> &'static str {
+        let names = match top {
+            HexTop::FLAT => ["E", "SE", "SW", "W", "NW", "NE"],
+            HexTop::POINTY => ["NE", "SE", "S", "SW", "NW", "N"],
+        };
+        names[self.index()]
+    }
+
+    /// Diagonal in which `to` lies from `from`, if they share only a corner.
+    pub fn between(from: Axial, to: Axial) -> Option<Self> {
+        let d = to - from;
+        DIAGONALS.iter().copied().find(|diag| diag.offset() == d)
+    }
+
+    /// The two directions whose edges meet in this corner.
+    pub fn directions(&self) -> (HexDirection, HexDirection) {
+        let second = DIRECTIONS[self.index()];
+        (second.rotate_ccw(), second)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex::Props;
+    use crate::Hexagons;
+
+    #[test]
+    fn test_direction() {
+        let a = Axial::new(3, -2);
+        let n: Vec<Axial> = HexDirection::all().iter().map(|dir| a.neighbour(*dir)).collect();
+        let mut expected: Vec<Axial> = a.neighbours().collect();
+        expected.rotate_right(1);
+        assert_eq!(n, expected);
+        for dir in HexDirection::all() {
+            assert_eq!(dir.opposite().opposite(), dir);
+            assert_eq!(dir.offset() + dir.opposite().offset(), Axial::default());
+            assert_eq!(dir.rotate_cw().offset(), dir.offset().rotate_cw());
+            assert_eq!(dir.rotate_ccw().rotate_cw(), dir);
+            assert_eq!(HexDirection::between(a, a.neighbour(dir)), Some(dir));
+            assert_eq!(a.direction_to(a.neighbour(dir)), Some(dir));
+        }
+        assert_eq!(HexDirection::between(a, a), None);
+        assert_eq!(HexDirection::between(a, a + Axial::new(2, 0)), None);
+        assert_eq!(HexDirection::QS.rotate(-7), HexDirection::QR);
+    }
+
+    #[test]
+    fn test_diagonal() {
+        let a = Axial::new(-1, 4);
+        for diag in HexDiagonal::all() {
+            let d = a.diagonal(diag);
+            assert_eq!(d.distance_to(a), 2);
+            assert_eq!(diag.opposite().offset(), Axial::default() - diag.offset());
+            assert_eq!(diag.rotate_cw().offset(), diag.offset().rotate_cw());
+            assert_eq!(HexDiagonal::between(a, d), Some(diag));
+            let (d1, d2) = diag.directions();
+            assert_eq!(d1.offset() + d2.offset(), diag.offset());
+            assert_eq!(d1.corners().1, diag);
+            assert_eq!(d2.corners().0, diag);
+        }
+        assert_eq!(HexDiagonal::between(a, a.neighbour(HexDirection::QS)), None);
+    }
+
+    #[test]
+    fn test_names() {
+        // y points down
+        let compass = |(x, y): (f32, f32)| {
+            let ns = if y < -0.1 { "N" } else if y > 0.1 { "S" } else { "" };
+            let ew = if x > 0.1 { "E" } else if x < -0.1 { "W" } else { "" };
+            format!("{}{}", ns, ew)
+        };
+        for (top, h) in [(HexTop::FLAT, Props::flat(1.5f32, 1.732f32)), (HexTop::POINTY, Props::pointy(1.732f32, 1.5f32))] {
+            for dir in HexDirection::all() {
+                assert_eq!(dir.name(top), compass(h.xy_ref(&dir.offset())), "{:?} {:?}", top, dir);
+            }
+            for diag in HexDiagonal::all() {
+                assert_eq!(diag.name(top), compass(h.xy_ref(&diag.offset())), "{:?} {:?}", top, diag);
+            }
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use std::fmt;
+
+/// Errors when building hexagons.
+/// A line is a row of a flat top hexagon or a column of a pointy top hexagon.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// There are no extends or no pixel reaches the alpha threshold.
+    Empty,
+    /// The mask data does not match width times height, `expected` is `usize::MAX` if that overflows.
+    MaskSize { expected: usize, found: usize },
+    /// The anchor is outside of the mask or the line through it has no pixels.
+    Anchor,
+    /// The given line is not a single run of pixels or does not follow the line before.
+    NotContiguous(usize),
+    /// The given line ends before it starts.
+    Inverted(usize),
+    /// A spacing or extend is NaN or infinite.
+    NotFinite,
+    /// A spacing is zero or negative.
+    Spacing,
+    /// The number of line extends does not match the range of lines.
+    ExtendsLength { expected: usize, found: usize },
+    /// The pixel (x, y) relative to the reference is not covered by any hexagon.
+    Gap((i32, i32)),
+    /// The pixel (x, y) relative to the reference is covered by more than one hexagon.
+    Overlap((i32, i32)),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Empty => write!(f, "hexagon has no pixels"),
+            Error::MaskSize { expected, found } => write!(f, "mask has {} bytes, expected {}", found, expected),
+            Error::Anchor => write!(f, "anchor is outside of the mask or the hexagon"),
+            Error::NotContiguous(l) => write!(f, "line {} is not contiguous", l),
+            Error::Inverted(l) => write!(f, "line {} ends before it starts", l),
+            Error::NotFinite => write!(f, "spacings and extends must be finite"),
+            Error::Spacing => write!(f, "spacings must be positive"),
+            Error::ExtendsLength { expected, found } => write!(f, "{} line extends given, expected {}", found, expected),
+            Error::Gap((x, y)) => write!(f, "pixel ({},{}) is not covered by any hexagon", x, y),
+            Error::Overlap((x, y)) => write!(f, "pixel ({},{}) is covered by more than one hexagon", x, y),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
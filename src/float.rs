@@ -0,0 +1,75 @@
+//! Floating point types usable for pixel coordinates and fractional hexagons.
+use std::fmt::Debug;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// The operations needed from `f32` and `f64`.
+/// Sealed, the crate relies on these two being the only implementations.
+pub trait Float:
+    sealed::Sealed + Copy + Default + Debug + PartialEq + PartialOrd
+    + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Div<Output = Self> + Neg<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+    const HALF: Self;
+
+    fn from_f64(v: f64) -> Self;
+
+    fn from_i32(v: i32) -> Self;
+
+    /// Converts to `i32`, saturating at the limits and giving 0 for NaN like `as`.
+    fn to_i32(self) -> i32;
+
+    fn from_f32(v: f32) -> Self;
+
+    /// Converts to `f32`, rounding to the nearest value.
+    fn to_f32(self) -> f32;
+
+    fn round(self) -> Self;
+
+    fn abs(self) -> Self;
+
+    fn recip(self) -> Self;
+
+    fn sin_cos(self) -> (Self, Self);
+
+    fn to_radians(self) -> Self;
+}
+
+macro_rules! impl_float {
+    ($t:ty) => {
+        impl sealed::Sealed for $t {}
+
+        impl Float for $t {
+            const ZERO: Self = 0.0;
+            const ONE: Self = 1.0;
+            const HALF: Self = 0.5;
+
+            fn from_f64(v: f64) -> Self { v as $t }
+
+            fn from_i32(v: i32) -> Self { v as $t }
+
+            fn to_i32(self) -> i32 { self as i32 }
+
+            fn from_f32(v: f32) -> Self { v as $t }
+
+            fn to_f32(self) -> f32 { self as f32 }
+
+            fn round(self) -> Self { <$t>::round(self) }
+
+            fn abs(self) -> Self { <$t>::abs(self) }
+
+            fn recip(self) -> Self { <$t>::recip(self) }
+
+            fn sin_cos(self) -> (Self, Self) { <$t>::sin_cos(self) }
+
+            fn to_radians(self) -> Self { <$t>::to_radians(self) }
+        }
+    };
+}
+
+impl_float!(f32);
+impl_float!(f64);
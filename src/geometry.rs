@@ -0,0 +1,202 @@
+//! Identifiers for the edges and corners shared by neighbouring hexagons.
+//!
+//! Every edge belongs to two hexagons and every corner to three. The identifiers
+//! are the same no matter from which of these hexagons they are created, so the
+//! positions computed from them agree exactly.
+use super::axial::Axial;
+use super::direction::{HexDiagonal, HexDirection};
+use super::Hexagons;
+
+/// An edge, stored as the edge of one hexagon in direction `QS`, `RS` or `RQ`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EdgeId {
+    qr: Axial,
+    direction: HexDirection,
+}
+
+/// A corner, stored as the corner of one hexagon in diagonal `PlusQ` or `MinusS`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CornerId {
+    qr: Axial,
+    diagonal: HexDiagonal,
+}
+
+impl EdgeId {
+    /// The edge between `qr` and its neighbour in `direction`.
+    pub fn new(qr: Axial, direction: HexDirection) -> Self {
+        match direction {
+            HexDirection::QS | HexDirection::RS | HexDirection::RQ => EdgeId { qr, direction },
+            _ => EdgeId { qr: qr.neighbour(direction), direction: direction.opposite() },
+        }
+    }
+
+    /// The hexagon the edge is stored with.
+    pub fn hex(&self) -> Axial {
+        self.qr
+    }
+
+    pub fn direction(&self) -> HexDirection {
+        self.direction
+    }
+
+    /// The two hexagons sharing the edge.
+    pub fn hexes(&self) -> [Axial; 2] {
+        [self.qr, self.qr.neighbour(self.direction)]
+    }
+
+    /// The corners at both ends of the edge.
+    pub fn corners(&self) -> [CornerId; 2] {
+        let (a, b) = self.direction.corners();
+        [CornerId::new(self.qr, a), CornerId::new(self.qr, b)]
+    }
+}
+
+impl CornerId {
+    /// The corner of `qr` in `diagonal`.
+    pub fn new(qr: Axial, diagonal: HexDiagonal) -> Self {
+        let target = match diagonal {
+            HexDiagonal::PlusQ | HexDiagonal::PlusR | HexDiagonal::PlusS => HexDiagonal::PlusQ,
+            _ => HexDiagonal::MinusS,
+        };
+        // the corner lies at `qr + offset / 3`, count in thirds of hexagons
+        let (q, r) = qr.to_tuple();
+        let (dq, dr) = (diagonal.offset() - target.offset()).to_tuple();
+        let q = (3 * q as i64 + dq as i64) / 3;
+        let r = (3 * r as i64 + dr as i64) / 3;
+        CornerId { qr: Axial::new(q as i32, r as i32), diagonal: target }
+    }
+
+    /// The hexagon the corner is stored with.
+    pub fn hex(&self) -> Axial {
+        self.qr
+    }
+
+    pub fn diagonal(&self) -> HexDiagonal {
+        self.diagonal
+    }
+
+    /// The three hexagons sharing the corner.
+    pub fn hexes(&self) -> [Axial; 3] {
+        let (a, b) = self.diagonal.directions();
+        [self.qr, self.qr.neighbour(a), self.qr.neighbour(b)]
+    }
+}
+
+/// Position of a corner in the lattice spanned by the neighbours of `(0, 0)`.
+/// For hexagons which are not regular this only approximates the corner.
+pub(crate) fn lattice_corner<H: Hexagons + ?Sized>(hexagons: &H, corner: CornerId) -> (f32, f32) {
+    let (x0, y0) = hexagons.xy_ref(&Axial::default());
+    let (xq, yq) = hexagons.xy_ref(&Axial::new(1, 0));
+    let (xr, yr) = hexagons.xy_ref(&Axial::new(0, 1));
+    let (xc, yc) = hexagons.xy_ref(&corner.hex());
+    // divide last, so corners at whole pixels stay exact
+    let (a, b) = corner.diagonal().offset().to_f32s();
+    (xc + (a * (xq - x0) + b * (xr - x0)) / 3f32, yc + (a * (yq - y0) + b * (yr - y0)) / 3f32)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex::Props;
+    use crate::ideal::HexProps;
+    use crate::pixelhex::PixelHex;
+
+    #[test]
+    fn test_ids() {
+        let qr = Axial::new(4, -7);
+        for dir in HexDirection::all() {
+            let id = EdgeId::new(qr, dir);
+            assert_eq!(id, EdgeId::new(qr.neighbour(dir), dir.opposite()));
+            assert!(id.hexes().contains(&qr));
+            assert!(id.hexes().contains(&qr.neighbour(dir)));
+            let (a, b) = dir.corners();
+            assert!(id.corners().contains(&CornerId::new(qr, a)));
+            assert!(id.corners().contains(&CornerId::new(qr, b)));
+        }
+        for diag in HexDiagonal::all() {
+            let id = CornerId::new(qr, diag);
+            let (a, b) = diag.directions();
+            assert_eq!(id, CornerId::new(qr.neighbour(a), diag.rotate(2)));
+            assert_eq!(id, CornerId::new(qr.neighbour(b), diag.rotate(-2)));
+            assert!(id.hexes().contains(&qr));
+            assert!(id.hexes().contains(&qr.neighbour(a)));
+            assert!(id.hexes().contains(&qr.neighbour(b)));
+        }
+        assert_eq!(CornerId::new(qr, HexDiagonal::MinusQ).hex(), Axial::new(3, -7));
+    }
+
+    /// Neighbours agree on shared corners and edges, and the edges form the outline.
+    fn check_shared<H: Hexagons>(h: &H) {
+        for qr in Axial::new(1, -2).range(3) {
+            for diag in HexDiagonal::all() {
+                let (a, b) = diag.directions();
+                let xy = h.corner(&qr, diag);
+                assert_eq!(h.corner(&qr.neighbour(a), diag.rotate(2)), xy);
+                assert_eq!(h.corner(&qr.neighbour(b), diag.rotate(-2)), xy);
+                assert!(h.corners(&qr).contains(&xy));
+            }
+            let mut outline = Vec::new();
+            for dir in HexDirection::all() {
+                let edge = h.edge(&qr, dir);
+                let (a, b) = dir.corners();
+                assert_eq!(edge.first(), Some(&h.corner(&qr, a)));
+                assert_eq!(edge.last(), Some(&h.corner(&qr, b)));
+                let mut back = h.edge(&qr.neighbour(dir), dir.opposite());
+                back.reverse();
+                assert_eq!(back, edge);
+                outline.extend_from_slice(&edge[1..]);
+            }
+            let corners = h.corners(&qr);
+            let start = corners.iter().position(|xy| *xy == outline[0]).unwrap();
+            assert_eq!(outline.len(), corners.len());
+            assert!(outline.iter().enumerate().all(|(i, xy)| *xy == corners[(start + i) % corners.len()]));
+        }
+    }
+
+    #[test]
+    fn test_props() {
+        let h = Props::flat(6f32, 10f32);
+        assert_eq!(h.corners(&Axial::default()), vec![
+            (4f32, 0f32), (2f32, 5f32), (-2f32, 5f32),
+            (-4f32, 0f32), (-2f32, -5f32), (2f32, -5f32),
+        ]);
+        assert_eq!(h.edge(&Axial::default(), HexDirection::RS), vec![(2f32, 5f32), (-2f32, 5f32)]);
+        check_shared(&h);
+        check_shared(&Props::pointy(10.3f32, 7.1f32));
+    }
+
+    #[test]
+    fn test_ideal() {
+        for h in [HexProps::flat(13f32), HexProps::pointy(13f32)] {
+            let corners = h.corners(&Axial::default());
+            for (diag, xy) in HexDiagonal::all().iter().zip(&corners) {
+                let (x, y) = lattice_corner(&h, CornerId::new(Axial::default(), *diag));
+                assert!((xy.0 - x).abs() < 1e-3 && (xy.1 - y).abs() < 1e-3, "{:?} {:?}", diag, xy);
+            }
+            check_shared(&h);
+        }
+    }
+
+    #[test]
+    fn test_pixelhex() {
+        let v = [
+            (-1.0f32, ( 0f32, 1f32)),
+            ( 0.0f32, (-1f32, 2f32)),
+            ( 1.0f32, (-1f32, 2f32)),
+            ( 2.0f32, ( 0f32, 1f32))
+        ];
+        let h = PixelHex::flat(&v);
+        assert_eq!(h.corners(&Axial::default()), vec![
+            (-0.5f32, -1.5f32), (1.5f32, -1.5f32), (1.5f32, -0.5f32), (2.5f32, -0.5f32),
+            (2.5f32, 0.5f32), (2.5f32, 1.5f32), (1.5f32, 1.5f32), (1.5f32, 2.5f32),
+            (-0.5f32, 2.5f32), (-0.5f32, 1.5f32), (-1.5f32, 1.5f32), (-1.5f32, 0.5f32),
+            (-1.5f32, -0.5f32), (-0.5f32, -0.5f32),
+        ]);
+        // the neighbours meet in the middle of the sides
+        assert_eq!(h.corner(&Axial::default(), HexDiagonal::PlusQ), (2.5f32, 0.5f32));
+        assert_eq!(h.edge(&Axial::default(), HexDirection::QR), vec![(1.5f32, -1.5f32), (1.5f32, -0.5f32), (2.5f32, -0.5f32), (2.5f32, 0.5f32)]);
+        check_shared(&h);
+        check_shared(&PixelHex::pointy(&v));
+    }
+}
@@ -1,19 +1,21 @@
 use serde::{Deserialize, Serialize};
 
-/// Pixel hexagons might have a bit different spacings.
-
 use super::axial::Axial;
+use super::cube::{round_cube, FractionalAxial};
+use super::float::Float;
 use super::{HexTop, Hexagons};
 
+/// Pixel hexagons might have a bit different spacings.
+/// Use `f64` for maps which reach far away from the origin.
 #[derive(Debug, Deserialize, Serialize)]
-pub struct Props {
+pub struct Props<F = f32> {
     top: HexTop,
-    vert_spacing: f32,
-    horz_spacing: f32,
+    vert_spacing: F,
+    horz_spacing: F,
 }
 
-impl Props {
-    pub fn new(top: HexTop, vert_spacing: f32, horz_spacing: f32) -> Self {
+impl<F: Float> Props<F> {
+    pub fn new(top: HexTop, vert_spacing: F, horz_spacing: F) -> Self {
         Props {
             top,
             vert_spacing,
@@ -21,43 +23,100 @@ impl Props {
         }
     }
 
-    pub fn flat(hs: f32, vs: f32) -> Self {
+    pub fn flat(hs: F, vs: F) -> Self {
         Props::new(HexTop::FLAT, vs, hs)
     }
 
-    pub fn pointy(hs: f32, vs: f32) -> Self {
+    pub fn pointy(hs: F, vs: F) -> Self {
         Props::new(HexTop::POINTY, vs, hs)
     }
 
-    fn xy_flat(&self, qr: &Axial) -> (f32, f32) {
-        let (qf, rf) = qr.to_f32s();
+    pub fn top(&self) -> HexTop {
+        self.top
+    }
+
+    fn xy_flat(&self, qr: &Axial) -> (F, F) {
+        let (qf, rf) = FractionalAxial::<F>::from(*qr).to_tuple();
         let x = qf * self.horz_spacing;
-        let y = (0.5f32 * qf + rf) * self.vert_spacing;
+        let y = (F::HALF * qf + rf) * self.vert_spacing;
         (x, y)
     }
 
-    fn xy_pointy(&self, qr: &Axial) -> (f32, f32) {
-        let (qf, rf) = qr.to_f32s();
-        let x = (qf+rf/2f32) * self.horz_spacing;
+    fn xy_pointy(&self, qr: &Axial) -> (F, F) {
+        let (qf, rf) = FractionalAxial::<F>::from(*qr).to_tuple();
+        let x = (qf + rf * F::HALF) * self.horz_spacing;
         let y = rf * self.vert_spacing;
         (x, y)
     }
 
-    fn pointy_qr_from_xy(&self, (x, y): (f32, f32)) -> Axial {
-        let q =  self.horz_spacing.recip() * x - 0.5f32 * self.vert_spacing.recip() * y;
+    fn pointy_qr_from_xy(&self, (x, y): (F, F)) -> FractionalAxial<F> {
+        let q =  self.horz_spacing.recip() * x - F::HALF * self.vert_spacing.recip() * y;
         let r = self.vert_spacing.recip() * y;
-        Axial::from((q, r))
+        FractionalAxial::new(q, r)
     }
 
-    fn flat_qr_from_xy(&self, (x, y): (f32, f32)) -> Axial {
+    fn flat_qr_from_xy(&self, (x, y): (F, F)) -> FractionalAxial<F> {
         let q = self.horz_spacing.recip() * x;
-        let r = - 0.5f32 * self.horz_spacing.recip() * x + self.vert_spacing.recip() * y;
-        Axial::from((q, r))
+        let r = - F::HALF * self.horz_spacing.recip() * x + self.vert_spacing.recip() * y;
+        FractionalAxial::new(q, r)
+    }
+
+    /// Compute the fractional axial coordinates of a x,y coordinate.
+    pub fn fractional_axial(&self, xy: (F, F)) -> FractionalAxial<F> {
+        match self.top {
+            HexTop::FLAT => self.flat_qr_from_xy(xy),
+            HexTop::POINTY => self.pointy_qr_from_xy(xy),
+        }
+    }
+
+    /// Compute the x,y reference of a hexagon in the precision of `F`.
+    pub fn xy_ref_f(&self, qr: &Axial) -> (F, F) {
+        match self.top {
+            HexTop::FLAT => self.xy_flat(qr),
+            HexTop::POINTY => self.xy_pointy(qr),
+        }
+    }
+
+    /// Compute the axial index of a hexagon from a x,y coordinate in the precision of `F`.
+    pub fn axial_f(&self, xy: (F, F)) -> Axial {
+        self.fractional_axial(xy).round()
     }
 
 }
 
+/// Number of values converted together by the batch functions.
+const LANES: usize = 8;
+
+/// Same as `f32::round`, but without a library call so that loops can be vectorized.
+#[inline]
+fn round_half_away(v: f32) -> f32 {
+    const TWO_POW_23: f32 = 8388608f32;
+    let a = v.abs();
+    // adding 2^23 rounds to a whole number with ties to even, ties are then moved up
+    let even = (a + TWO_POW_23) - TWO_POW_23;
+    let r = if a - even == 0.5f32 { even + 1f32 } else { even };
+    // larger values are whole numbers already, NaN stays NaN
+    if a < TWO_POW_23 { r.copysign(v) } else { v }
+}
+
+/// Cube rounding of `LANES` fractional hexagons at once, with the rounding of
+/// `FractionalCube::round` but without its library calls.
+fn round_lanes(q_f: &[f32; LANES], r_f: &[f32; LANES], out: &mut [Axial]) {
+    let mut q_out = [0f32; LANES];
+    let mut r_out = [0f32; LANES];
+    for i in 0..LANES {
+        (q_out[i], r_out[i]) = round_cube(q_f[i], r_f[i], -q_f[i] - r_f[i], round_half_away);
+    }
+    for (i, qr) in out.iter_mut().enumerate() {
+        *qr = Axial::new(q_out[i] as i32, r_out[i] as i32);
+    }
+}
+
 impl Hexagons for Props {
+    fn top(&self) -> HexTop {
+        self.top
+    }
+
     fn horizontal_spacing(&self) -> f32 {
         self.horz_spacing
     }
@@ -67,10 +126,7 @@ impl Hexagons for Props {
     }
 
     fn xy_ref(&self, qr: &Axial) -> (f32, f32) {
-        match self.top {
-            HexTop::FLAT => self.xy_flat(qr),
-            HexTop::POINTY => self.xy_pointy(qr),
-        }
+        self.xy_ref_f(qr)
     }
 
     fn xy_relative(&self, xy: (f32, f32)) -> (f32, f32) {
@@ -79,13 +135,76 @@ impl Hexagons for Props {
     }
 
     fn axial(&self, xy: (f32, f32)) -> Axial {
+        self.axial_f(xy)
+    }
+
+    /// Converts `LANES` coordinates at once, with the same results as `axial`.
+    fn axial_batch(&self, xy: &[(f32, f32)], out: &mut [Axial]) {
+        assert_eq!(xy.len(), out.len(), "axial_batch needs slices of the same length");
+        let hs = self.horz_spacing.recip();
+        let vs = self.vert_spacing.recip();
+        let flat = self.top == HexTop::FLAT;
+        let mut xy_chunks = xy.chunks_exact(LANES);
+        let mut out_chunks = out.chunks_exact_mut(LANES);
+        for (xy, out) in (&mut xy_chunks).zip(&mut out_chunks) {
+            let mut q = [0f32; LANES];
+            let mut r = [0f32; LANES];
+            for i in 0..LANES {
+                let (x, y) = xy[i];
+                if flat {
+                    q[i] = hs * x;
+                    r[i] = - 0.5f32 * hs * x + vs * y;
+                } else {
+                    q[i] = hs * x - 0.5f32 * vs * y;
+                    r[i] = vs * y;
+                }
+            }
+            round_lanes(&q, &r, out);
+        }
+        for (qr, xy) in out_chunks.into_remainder().iter_mut().zip(xy_chunks.remainder()) {
+            *qr = self.axial(*xy);
+        }
+    }
+
+    fn xy_ref_batch(&self, qr: &[Axial], out: &mut [(f32, f32)]) {
+        assert_eq!(qr.len(), out.len(), "xy_ref_batch needs slices of the same length");
         match self.top {
-            HexTop::FLAT => self.flat_qr_from_xy(xy),
-            HexTop::POINTY => self.pointy_qr_from_xy(xy),
+            HexTop::FLAT => out.iter_mut().zip(qr).for_each(|(xy, qr)| *xy = self.xy_flat(qr)),
+            HexTop::POINTY => out.iter_mut().zip(qr).for_each(|(xy, qr)| *xy = self.xy_pointy(qr)),
         }
     }
 }
 
+/// Computes the hexagons in double precision, only the pixel coordinates are `f32`.
+impl Hexagons for Props<f64> {
+    fn top(&self) -> HexTop {
+        self.top
+    }
+
+    fn horizontal_spacing(&self) -> f32 {
+        self.horz_spacing as f32
+    }
+
+    fn vertical_spacing(&self) -> f32 {
+        self.vert_spacing as f32
+    }
+
+    fn xy_ref(&self, qr: &Axial) -> (f32, f32) {
+        let (x, y) = self.xy_ref_f(qr);
+        (x as f32, y as f32)
+    }
+
+    fn xy_relative(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        let (x, y) = (x as f64, y as f64);
+        let (xc, yc) = self.xy_ref_f(&self.axial_f((x, y)));
+        ((x - xc) as f32, (y - yc) as f32)
+    }
+
+    fn axial(&self, (x, y): (f32, f32)) -> Axial {
+        self.axial_f((x as f64, y as f64))
+    }
+}
+
 
 #[cfg(test)]
 mod test {
@@ -106,5 +225,72 @@ mod test {
         assert_eq!(h.axial((2f32, 2f32)), Axial::new(0, 0));
         assert_eq!(h.axial((7f32, 15f32)), Axial::new(1, 1));
         assert_eq!(h.axial((-13f32, 1f32)), Axial::new(-2, 1));
+        assert_eq!(h.fractional_axial((7f32, 15f32)), FractionalAxial::new(1f32, 1f32));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_f64() {
+        let h = Props::<f64>::flat(7.0, 10.0);
+        let p = Props::flat(7f32, 10f32);
+        let center = Axial::new(10_000_000, -10_000_000);
+        let offsets = [(0.0, 0.0), (2.4, 0.0), (-2.4, 1.5), (1.0, -4.0), (-3.0, 3.0)];
+        for qr in center.range(2) {
+            let (x, y) = h.xy_ref_f(&qr);
+            for (dx, dy) in offsets {
+                assert_eq!(h.axial_f((x + dx, y + dy)), qr);
+            }
+        }
+        // f32 only resolves steps of 4 units around 5 * 10^7, hexagons are lost already on the round trip
+        assert!(center.range(2).any(|qr| p.axial(p.xy_ref(&qr)) != qr));
+
+        // as `Hexagons` the pixels are f32, but the hexagons are not lost near the origin
+        let near = Axial::new(1_000, -3_000);
+        for qr in near.range(2) {
+            assert_eq!(h.axial(h.xy_ref(&qr)), qr);
+            assert_eq!(h.xy_ref(&qr), p.xy_ref(&qr));
+            assert_eq!(h.xy_relative(h.xy_ref(&qr)), (0f32, 0f32));
+        }
+        assert_eq!((h.horizontal_spacing(), h.vertical_spacing()), (7f32, 10f32));
+    }
+
+    #[test]
+    fn test_batch() {
+        // a grid over several hexagons, with ties on the borders, huge values and NaN
+        let mut xy: Vec<(f32, f32)> = (-40..40)
+            .flat_map(|y| (-40..40).map(move |x| (x as f32 * 0.75f32, y as f32 * 0.625f32)))
+            .collect();
+        xy.extend([(f32::NAN, 1f32), (1e30f32, -1e30f32), (f32::INFINITY, 0f32), (3.5f32, 7.5f32)]);
+        for h in [Props::flat(7f32, 10f32), Props::pointy(10.3f32, 7.1f32)] {
+            for n in [0, 1, 7, 8, 9, xy.len()] {
+                let mut batch = vec![Axial::default(); n];
+                h.axial_batch(&xy[..n], &mut batch);
+                let scalar: Vec<Axial> = xy[..n].iter().map(|xy| h.axial(*xy)).collect();
+                assert_eq!(batch, scalar);
+
+                let mut refs = vec![(0f32, 0f32); n];
+                h.xy_ref_batch(&batch, &mut refs);
+                for (qr, (x, y)) in batch.iter().zip(&refs) {
+                    let (xs, ys) = h.xy_ref(qr);
+                    assert_eq!((x.to_bits(), y.to_bits()), (xs.to_bits(), ys.to_bits()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_round() {
+        let values = [0f32, -0f32, 0.5f32, -0.5f32, 0.49999997f32, -0.49999997f32, 1.5f32, 2.5f32, -2.5f32,
+            8388607.5f32, -8388607.5f32, 8388608f32, 1e30f32, f32::INFINITY, f32::NEG_INFINITY, 1.0000001f32];
+        for v in values.into_iter().chain((-2000..2000).map(|i| i as f32 * 0.0625f32 + 0.001f32)) {
+            assert_eq!(round_half_away(v).to_bits(), v.round().to_bits(), "{}", v);
+        }
+        assert!(round_half_away(f32::NAN).is_nan());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_batch_length() {
+        let mut out = [Axial::default(); 2];
+        Props::flat(7f32, 10f32).axial_batch(&[(0f32, 0f32)], &mut out);
+    }
+}
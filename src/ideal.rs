@@ -1,112 +1,236 @@
-/// An ideal hexagon
-/// Following the great page https://www.redblobgames.com/grids/hexagons/
+//! An ideal hexagon
+//! Following the great page https://www.redblobgames.com/grids/hexagons/
+use serde::{Deserialize, Serialize};
+
 use super::axial::Axial;
-use super::HexTop;
+use super::cube::FractionalAxial;
+use super::direction::HexDiagonal;
+use super::float::Float;
+use super::geometry::CornerId;
+use super::{HexTop, Hexagons};
 
 
 /// Properties of a hexagon with a given size.
-#[derive(Copy, Clone, Debug)]
-pub struct HexProps {
+#[derive(Copy, Clone, Debug, Deserialize, Serialize)]
+pub struct HexProps<F = f32> {
     top: HexTop,
-    size: f32,
-    width: f32,
-    height: f32,
-    inner_radius: f32,
-    outer_radius: f32,
-    vert_spacing: f32,
-    horz_spacing: f32,
-    points: [(f32, f32);6],
+    size: F,
+    width: F,
+    height: F,
+    inner_radius: F,
+    outer_radius: F,
+    vert_spacing: F,
+    horz_spacing: F,
+    points: [(F, F);6],
 }
 
-const SQRT3 : f32 = 1.732050807568877193176604123436845839023590087890625f32;
-const SQRT3BY2 : f32 = SQRT3/2f32;
-const SQRT3BY3 : f32 = SQRT3/3f32;
-const ONETHIRD : f32 = 1f32/3f32;
-const TWOTHIRD : f32 = 2f32/3f32;
+const SQRT3 : f64 = 1.732050807568877293527446341505872366942805253810380628055806f64;
 
-impl HexProps {
+impl<F: Float> HexProps<F> {
 
     /// Creates `HexProps` for a flat top hexagon with `size`.
     /// The size is the radius of the outer circle which goes through the edges of the hexagon.
-    pub fn flat(size: f32) -> Self {
-        let mut pts = [(0f32, 0f32); 6];
-        for i in 0..=5 {
-            let ang = (i as f32 * 60f32).to_radians();
-            pts[i] = (size*ang.cos(), size*ang.sin());
+    pub fn flat(size: F) -> Self {
+        let sqrt3 = F::from_f64(SQRT3);
+        let two = F::from_i32(2);
+        let mut pts = [(F::ZERO, F::ZERO); 6];
+        for (i, pt) in pts.iter_mut().enumerate() {
+            let (sin, cos) = (F::from_i32(i as i32) * F::from_i32(60)).to_radians().sin_cos();
+            *pt = (size*cos, size*sin);
         }
         HexProps {
             top: HexTop::FLAT,
-            size: size,
-            width: 2f32 * size,
-            height: SQRT3 * size,
-            inner_radius: SQRT3/2f32 * size,
+            size,
+            width: two * size,
+            height: sqrt3 * size,
+            inner_radius: sqrt3/two * size,
             outer_radius: size,
-            vert_spacing: SQRT3 * size,
-            horz_spacing: 3f32/2f32 * size,
+            vert_spacing: sqrt3 * size,
+            horz_spacing: F::from_i32(3)/two * size,
             points: pts,
         }
     }
 
     /// Creates `HexProps` for a pointy top hexagon with `size`.
     /// The size is the radius of the outer circle which goes through the edges of the hexagon.
-    pub fn pointy(size: f32) -> Self {
-        let mut pts = [(0f32, 0f32); 6];
-        for i in 0..=5 {
-            let ang = (i as f32 * 60f32 + 30f32).to_radians();
-            pts[i] = (size*ang.cos(), size*ang.sin());
+    pub fn pointy(size: F) -> Self {
+        let sqrt3 = F::from_f64(SQRT3);
+        let two = F::from_i32(2);
+        let mut pts = [(F::ZERO, F::ZERO); 6];
+        for (i, pt) in pts.iter_mut().enumerate() {
+            let (sin, cos) = (F::from_i32(i as i32) * F::from_i32(60) + F::from_i32(30)).to_radians().sin_cos();
+            *pt = (size*cos, size*sin);
         }
         HexProps {
             top: HexTop::POINTY,
-            size: size,
-            width: SQRT3 * size,
-            height: 2f32 * size,
-            inner_radius: SQRT3/2f32 * size,
+            size,
+            width: sqrt3 * size,
+            height: two * size,
+            inner_radius: sqrt3/two * size,
             outer_radius: size,
-            vert_spacing: 3f32/2f32 * size,
-            horz_spacing: SQRT3 * size,
+            vert_spacing: F::from_i32(3)/two * size,
+            horz_spacing: sqrt3 * size,
             points: pts,
         }
     }
 
-    pub fn size(&self) -> f32 { self.size }
+    pub fn size(&self) -> F { self.size }
 
-    pub fn points(&self) -> &[(f32, f32)] { &self.points }
+    pub fn points(&self) -> &[(F, F)] { &self.points }
 
     /// Outer radius of the hexagon
-    pub fn outer(&self) -> f32 { self.outer_radius }
+    pub fn outer(&self) -> F { self.outer_radius }
 
     /// Convert axial (q,r) coordinates into (x,y) coordinates for the hexagons center.
-    pub fn axial2xy(&self, qr : Axial) -> (f32, f32) {
-        let qr = qr.to_f32s();
+    pub fn axial2xy(&self, qr : Axial) -> (F, F) {
+        let qr = FractionalAxial::<F>::from(qr).to_tuple();
+        let sqrt3 = F::from_f64(SQRT3);
+        let sqrt3by2 = sqrt3 / F::from_i32(2);
         let mat = match self.top {
-            HexTop::FLAT => [[1.5f32, SQRT3BY2], [0f32, SQRT3]],
-            HexTop::POINTY => [[SQRT3, 0f32], [SQRT3BY2, 1.5f32]],
+            HexTop::FLAT => [[F::from_f64(1.5), F::ZERO], [sqrt3by2, sqrt3]],
+            HexTop::POINTY => [[sqrt3, sqrt3by2], [F::ZERO, F::from_f64(1.5)]],
         };
         let x = self.size * (mat[0][0]*qr.0+mat[0][1]*qr.1);
         let y = self.size * (mat[1][0]*qr.0+mat[1][1]*qr.1);
         (x, y)
     }
 
-    // Convert pixel coordinates to axial coordinates
-    pub fn xy2axial(&self, xy : impl Into<(f32, f32)>) -> Axial {
+    /// Convert pixel coordinates to axial coordinates.
+    pub fn xy2axial(&self, xy : impl Into<(F, F)>) -> Axial {
+        self.xy2fractional(xy).round()
+    }
+
+    /// Convert pixel coordinates to fractional axial coordinates.
+    pub fn xy2fractional(&self, xy : impl Into<(F, F)>) -> FractionalAxial<F> {
         let xy = xy.into();
+        let sqrt3by3 = F::from_f64(SQRT3) / F::from_i32(3);
+        let one_third = F::ONE / F::from_i32(3);
+        let two_third = F::from_i32(2) / F::from_i32(3);
         let mat = match self.top {
-            HexTop::FLAT => [[TWOTHIRD, -ONETHIRD], [0f32, SQRT3BY3]],
-            HexTop::POINTY => [[SQRT3BY3, 0f32], [-ONETHIRD, TWOTHIRD]],
+            HexTop::FLAT => [[two_third, F::ZERO], [-one_third, sqrt3by3]],
+            HexTop::POINTY => [[sqrt3by3, -one_third], [F::ZERO, two_third]],
         };
         let qf = (mat[0][0]*xy.0+mat[0][1]*xy.1) / self.size;
         let rf = (mat[1][0]*xy.0+mat[1][1]*xy.1) / self.size;
-        Axial::from((qf,rf))
+        FractionalAxial::new(qf, rf)
     }
 
 }
 
+/// With `f64` the hexagons are computed in double precision, only the pixel coordinates are `f32`.
+impl<F: Float> Hexagons for HexProps<F> {
+    fn top(&self) -> HexTop {
+        self.top
+    }
+
+    fn horizontal_spacing(&self) -> f32 {
+        self.horz_spacing.to_f32()
+    }
+
+    fn vertical_spacing(&self) -> f32 {
+        self.vert_spacing.to_f32()
+    }
+
+    fn xy_ref(&self, qr: &Axial) -> (f32, f32) {
+        let (x, y) = self.axial2xy(*qr);
+        (x.to_f32(), y.to_f32())
+    }
+
+    fn xy_relative(&self, xy: (f32, f32)) -> (f32, f32) {
+        let (xc, yc) = self.xy_ref(&self.axial(xy));
+        (xy.0 - xc, xy.1 - yc)
+    }
+
+    fn axial(&self, (x, y): (f32, f32)) -> Axial {
+        self.xy2axial((F::from_f32(x), F::from_f32(y)))
+    }
+
+    fn corner(&self, qr: &Axial, diagonal: HexDiagonal) -> (f32, f32) {
+        let id = CornerId::new(*qr, diagonal);
+        // `points` starts at the east corner and goes clockwise
+        let i = match self.top {
+            HexTop::FLAT => id.diagonal().index(),
+            HexTop::POINTY => (id.diagonal().index() + 5) % 6,
+        };
+        let (x, y) = self.axial2xy(id.hex());
+        ((x + self.points[i].0).to_f32(), (y + self.points[i].1).to_f32())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::hex::Props;
 
     #[test]
     fn test_props() {
         assert_eq!(HexProps::flat(13.0f32).width, 26.0f32);
     }
+
+    fn assert_close((x1, y1): (f32, f32), (x2, y2): (f32, f32)) {
+        assert!((x1 - x2).abs() < 1e-3 && (y1 - y2).abs() < 1e-3, "({},{}) != ({},{})", x1, y1, x2, y2);
+    }
+
+    fn round_trip(h: &HexProps, p: &Props) {
+        assert_eq!(h.horizontal_spacing(), p.horizontal_spacing());
+        assert_eq!(h.vertical_spacing(), p.vertical_spacing());
+        for q in -5..=5 {
+            for r in -5..=5 {
+                let qr = Axial::new(q, r);
+                assert_close(h.xy_ref(&qr), p.xy_ref(&qr));
+                assert_eq!(h.axial(h.xy_ref(&qr)), qr);
+                assert_eq!(p.axial(h.xy_ref(&qr)), qr);
+                assert_eq!(h.axial(p.xy_ref(&qr)), qr);
+            }
+        }
+    }
+
+    #[test]
+    fn test_flat_like_props() {
+        let h = HexProps::flat(10f32);
+        let p = Props::flat(h.horz_spacing, h.vert_spacing);
+        round_trip(&h, &p);
+    }
+
+    #[test]
+    fn test_pointy_like_props() {
+        let h = HexProps::pointy(10f32);
+        let p = Props::pointy(h.horz_spacing, h.vert_spacing);
+        round_trip(&h, &p);
+    }
+
+    #[test]
+    fn test_relative() {
+        let h = HexProps::pointy(10f32);
+        let (x, y) = h.xy_ref(&Axial::new(2, -1));
+        assert_close(h.xy_relative((x + 1f32, y - 2f32)), (1f32, -2f32));
+        let corner = h.points()[0];
+        assert_eq!(h.axial((0.9 * corner.0, 0.9 * corner.1)), Axial::default());
+    }
+
+    #[test]
+    fn test_f64() {
+        let h = HexProps::<f64>::pointy(10.0);
+        for qr in Axial::new(-10_000_000, 10_000_000).range(2) {
+            let (x, y) = h.axial2xy(qr);
+            assert_eq!(h.xy2axial((x + 4.0, y - 3.0)), qr);
+        }
+
+        let h = HexProps::<f64>::flat(10.0);
+        let p = Props::flat(h.horizontal_spacing(), h.vertical_spacing());
+        for qr in Axial::new(3, -2).range(4) {
+            assert_close(h.xy_ref(&qr), p.xy_ref(&qr));
+            assert_eq!(h.axial(h.xy_ref(&qr)), qr);
+        }
+        assert_close(h.corner(&Axial::default(), HexDiagonal::PlusQ), (10f32, 0f32));
+    }
+
+    #[test]
+    fn test_serde() {
+        let h = HexProps::flat(13f32);
+        let s = ron::to_string(&h).unwrap();
+        let h2: HexProps = ron::from_str(&s).unwrap();
+        assert_eq!(h2.size(), h.size());
+        assert_eq!(h2.points(), h.points());
+    }
 }
\ No newline at end of file
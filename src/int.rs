@@ -0,0 +1,89 @@
+//! Integer types usable for axial coordinates.
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// The operations needed from `i8`, `i16`, `i32` and `i64`.
+/// Sealed, the crate relies on these four being the only implementations.
+pub trait Int:
+    sealed::Sealed + Copy + Default + Debug + Eq + Ord + Hash
+    + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Neg<Output = Self>
+    + AddAssign + SubAssign + MulAssign
+{
+    /// Type of lengths and distances.
+    type Unsigned: Copy + Debug + Eq + Ord + TryFrom<Self::Distance>;
+
+    /// Unsigned type twice as wide, which holds every cube distance.
+    type Distance: Copy + Debug + Eq + Ord;
+
+    const MIN: Self;
+    const MAX: Self;
+    /// Largest distance, where `length` and `distance_to` saturate.
+    const UNSIGNED_MAX: Self::Unsigned;
+
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+
+    fn checked_neg(self) -> Option<Self>;
+
+    fn wrapping_add(self, rhs: Self) -> Self;
+
+    fn wrapping_sub(self, rhs: Self) -> Self;
+
+    fn wrapping_mul(self, rhs: Self) -> Self;
+
+    fn wrapping_neg(self) -> Self;
+
+    /// Exact cube length of `qr` minus `qr0`, computed in a wider type.
+    fn cube_distance(qr: (Self, Self), qr0: (Self, Self)) -> Self::Distance;
+}
+
+macro_rules! impl_int {
+    ($t:ty, $u:ty, $wide:ty, $dist:ty) => {
+        impl sealed::Sealed for $t {}
+
+        impl Int for $t {
+            type Unsigned = $u;
+            type Distance = $dist;
+
+            const MIN: Self = <$t>::MIN;
+            const MAX: Self = <$t>::MAX;
+            const UNSIGNED_MAX: $u = <$u>::MAX;
+
+            fn checked_add(self, rhs: Self) -> Option<Self> { <$t>::checked_add(self, rhs) }
+
+            fn checked_sub(self, rhs: Self) -> Option<Self> { <$t>::checked_sub(self, rhs) }
+
+            fn checked_mul(self, rhs: Self) -> Option<Self> { <$t>::checked_mul(self, rhs) }
+
+            fn checked_neg(self) -> Option<Self> { <$t>::checked_neg(self) }
+
+            fn wrapping_add(self, rhs: Self) -> Self { <$t>::wrapping_add(self, rhs) }
+
+            fn wrapping_sub(self, rhs: Self) -> Self { <$t>::wrapping_sub(self, rhs) }
+
+            fn wrapping_mul(self, rhs: Self) -> Self { <$t>::wrapping_mul(self, rhs) }
+
+            fn wrapping_neg(self) -> Self { <$t>::wrapping_neg(self) }
+
+            fn cube_distance((q, r): (Self, Self), (q0, r0): (Self, Self)) -> Self::Distance {
+                let dq = q as $wide - q0 as $wide;
+                let dr = r as $wide - r0 as $wide;
+                // at most twice the range of `Self`, which fits the wide type and its unsigned half
+                dq.abs().max(dr.abs()).max((dq + dr).abs()) as $dist
+            }
+        }
+    };
+}
+
+impl_int!(i8, u8, i32, u16);
+impl_int!(i16, u16, i32, u32);
+impl_int!(i32, u32, i64, u64);
+impl_int!(i64, u64, i128, u128);
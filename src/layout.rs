@@ -0,0 +1,251 @@
+//! Place any `Hexagons` on the screen with an origin, a scale, a rotation and the direction of y.
+//!
+//! The pixel coordinates of the wrapped hexagons are mapped by the affine transform
+//! `origin + rotation * scale * flip * xy`. The inverse is computed once and cached.
+use super::axial::Axial;
+use super::direction::{HexDiagonal, HexDirection};
+use super::{HexTop, Hexagons};
+
+/// Hexagons `H` moved, scaled, rotated and possibly mirrored.
+#[derive(Debug, Clone)]
+pub struct Layout<H> {
+    hexagons: H,
+    origin: (f32, f32),
+    scale: (f32, f32),
+    rotation: f32,
+    flip_y: bool,
+    matrix: [[f32; 2]; 2],
+    inverse: [[f32; 2]; 2],
+}
+
+impl<H: Hexagons> Layout<H> {
+    /// Creates the identity layout of `hexagons`.
+    pub fn new(hexagons: H) -> Self {
+        let mut layout = Layout {
+            hexagons,
+            origin: (0f32, 0f32),
+            scale: (1f32, 1f32),
+            rotation: 0f32,
+            flip_y: false,
+            matrix: [[1f32, 0f32], [0f32, 1f32]],
+            inverse: [[1f32, 0f32], [0f32, 1f32]],
+        };
+        layout.update();
+        layout
+    }
+
+    /// Moves the hexagon `(0, 0)` to `origin`.
+    pub fn with_origin(mut self, origin: (f32, f32)) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    /// Scales x and y by the same `factor`.
+    pub fn with_zoom(self, factor: f32) -> Self {
+        self.with_scale((factor, factor))
+    }
+
+    /// Scales x and y independently.
+    ///
+    /// Panics if a factor is zero or not finite, the layout could not map the screen back.
+    pub fn with_scale(mut self, scale: (f32, f32)) -> Self {
+        assert!(
+            scale.0 != 0f32 && scale.1 != 0f32 && scale.0.is_finite() && scale.1.is_finite(),
+            "scale factors have to be finite and non-zero, got {:?}", scale
+        );
+        self.scale = scale;
+        self.update();
+        self
+    }
+
+    /// Rotates by `radians` around the origin, clockwise on a y down screen.
+    ///
+    /// Panics if `radians` is not finite.
+    pub fn with_rotation(mut self, radians: f32) -> Self {
+        assert!(radians.is_finite(), "rotation has to be finite, got {}", radians);
+        self.rotation = radians;
+        self.update();
+        self
+    }
+
+    /// Lets y point up instead of down.
+    pub fn with_flip_y(mut self, flip_y: bool) -> Self {
+        self.flip_y = flip_y;
+        self.update();
+        self
+    }
+
+    pub fn hexagons(&self) -> &H {
+        &self.hexagons
+    }
+
+    pub fn origin(&self) -> (f32, f32) {
+        self.origin
+    }
+
+    pub fn scale(&self) -> (f32, f32) {
+        self.scale
+    }
+
+    pub fn rotation(&self) -> f32 {
+        self.rotation
+    }
+
+    pub fn flip_y(&self) -> bool {
+        self.flip_y
+    }
+
+    /// Recomputes the matrix and its inverse, in double precision.
+    fn update(&mut self) {
+        let (sin, cos) = (self.rotation as f64).sin_cos();
+        let sx = self.scale.0 as f64;
+        let sy = if self.flip_y { -self.scale.1 as f64 } else { self.scale.1 as f64 };
+        let m = [[cos * sx, -sin * sy], [sin * sx, cos * sy]];
+        let det = m[0][0] * m[1][1] - m[0][1] * m[1][0];
+        let inv = [[m[1][1] / det, -m[0][1] / det], [-m[1][0] / det, m[0][0] / det]];
+        self.matrix = m.map(|row| row.map(|v| v as f32));
+        self.inverse = inv.map(|row| row.map(|v| v as f32));
+    }
+
+    /// Maps pixel coordinates of the wrapped hexagons to the screen.
+    pub fn to_screen(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        let m = &self.matrix;
+        (self.origin.0 + m[0][0] * x + m[0][1] * y, self.origin.1 + m[1][0] * x + m[1][1] * y)
+    }
+
+    /// Maps screen coordinates to pixel coordinates of the wrapped hexagons.
+    pub fn to_hexagons(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        let m = &self.inverse;
+        let (dx, dy) = (x - self.origin.0, y - self.origin.1);
+        (m[0][0] * dx + m[0][1] * dy, m[1][0] * dx + m[1][1] * dy)
+    }
+}
+
+/// The spacings are the ones of the wrapped hexagons times the scale and ignore the rotation.
+/// A y-flip or a negative scale mirrors the outlines, so they run counterclockwise on screen.
+/// `PixelHex` expects whole pixels, which rotations and most scales do not map to whole pixels.
+impl<H: Hexagons> Hexagons for Layout<H> {
+    fn top(&self) -> HexTop {
+        self.hexagons.top()
+    }
+
+    fn horizontal_spacing(&self) -> f32 {
+        self.hexagons.horizontal_spacing() * self.scale.0.abs()
+    }
+
+    fn vertical_spacing(&self) -> f32 {
+        self.hexagons.vertical_spacing() * self.scale.1.abs()
+    }
+
+    fn xy_ref(&self, qr: &Axial) -> (f32, f32) {
+        self.to_screen(self.hexagons.xy_ref(qr))
+    }
+
+    fn xy_relative(&self, xy: (f32, f32)) -> (f32, f32) {
+        let (xc, yc) = self.xy_ref(&self.axial(xy));
+        (xy.0 - xc, xy.1 - yc)
+    }
+
+    fn axial(&self, xy: (f32, f32)) -> Axial {
+        self.hexagons.axial(self.to_hexagons(xy))
+    }
+
+    fn contains(&self, qr: &Axial, xy: (f32, f32)) -> bool {
+        self.hexagons.contains(qr, self.to_hexagons(xy))
+    }
+
+    fn corner(&self, qr: &Axial, diagonal: HexDiagonal) -> (f32, f32) {
+        self.to_screen(self.hexagons.corner(qr, diagonal))
+    }
+
+    fn corners(&self, qr: &Axial) -> Vec<(f32, f32)> {
+        self.hexagons.corners(qr).into_iter().map(|xy| self.to_screen(xy)).collect()
+    }
+
+    fn edge(&self, qr: &Axial, direction: HexDirection) -> Vec<(f32, f32)> {
+        self.hexagons.edge(qr, direction).into_iter().map(|xy| self.to_screen(xy)).collect()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex::Props;
+    use crate::pixelhex::PixelHex;
+
+    fn assert_close((x1, y1): (f32, f32), (x2, y2): (f32, f32)) {
+        assert!((x1 - x2).abs() < 1e-3 && (y1 - y2).abs() < 1e-3, "({},{}) != ({},{})", x1, y1, x2, y2);
+    }
+
+    #[test]
+    fn test_identity() {
+        let l = Layout::new(Props::flat(7f32, 10f32));
+        let h = Props::flat(7f32, 10f32);
+        for qr in Axial::default().range(5) {
+            assert_eq!(l.xy_ref(&qr), h.xy_ref(&qr));
+        }
+        assert_eq!(l.axial((7f32, 15f32)), Axial::new(1, 1));
+    }
+
+    #[test]
+    fn test_transform() {
+        let l = Layout::new(Props::pointy(10f32, 8f32))
+            .with_origin((400f32, 300f32))
+            .with_scale((2f32, 0.5f32))
+            .with_flip_y(true);
+        assert_eq!(l.xy_ref(&Axial::default()), (400f32, 300f32));
+        assert_eq!(l.xy_ref(&Axial::new(1, 0)), (420f32, 300f32));
+        // y points up
+        assert_eq!(l.xy_ref(&Axial::new(0, 1)), (410f32, 296f32));
+        assert_eq!(l.horizontal_spacing(), 20f32);
+        assert_eq!(l.vertical_spacing(), 4f32);
+
+        let r = Layout::new(Props::flat(6f32, 10f32)).with_rotation(std::f32::consts::FRAC_PI_2);
+        assert_close(r.xy_ref(&Axial::new(0, 1)), (-10f32, 0f32));
+        assert_close(r.corner(&Axial::default(), HexDiagonal::PlusQ), (0f32, 4f32));
+        assert_eq!(r.axial((-10f32, 0f32)), Axial::new(0, 1));
+        assert!(r.contains(&Axial::new(0, 1), (-10f32, 0f32)));
+        assert!(!r.contains(&Axial::new(0, 1), (0f32, 0f32)));
+    }
+
+    #[test]
+    fn test_inverse() {
+        let v = [
+            (-1.0f32, ( 0f32, 1f32)),
+            ( 0.0f32, (-1f32, 2f32)),
+            ( 1.0f32, (-1f32, 2f32)),
+            ( 2.0f32, ( 0f32, 1f32))
+        ];
+        let layouts: Vec<Box<dyn Hexagons>> = vec![
+            Box::new(Layout::new(Props::flat(7f32, 10f32)).with_origin((-31.5f32, 12.25f32)).with_zoom(3.3f32)),
+            Box::new(Layout::new(Props::pointy(10f32, 7f32)).with_scale((0.7f32, 1.9f32)).with_rotation(0.4f32).with_flip_y(true)),
+            Box::new(Layout::new(PixelHex::flat(&v)).with_origin((100f32, 50f32)).with_flip_y(true).with_zoom(16f32)),
+        ];
+        for l in layouts {
+            for qr in Axial::new(3, -8).range(30) {
+                let xy = l.xy_ref(&qr);
+                assert_eq!(l.axial(xy), qr);
+                assert_close(l.xy_relative(xy), (0f32, 0f32));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "non-zero")]
+    fn test_zero_scale() {
+        let _ = Layout::new(Props::flat(7f32, 10f32)).with_scale((2f32, 0f32));
+    }
+
+    #[test]
+    #[should_panic(expected = "non-zero")]
+    fn test_infinite_zoom() {
+        let _ = Layout::new(Props::flat(7f32, 10f32)).with_zoom(f32::INFINITY);
+    }
+
+    #[test]
+    #[should_panic(expected = "finite")]
+    fn test_nan_rotation() {
+        let _ = Layout::new(Props::flat(7f32, 10f32)).with_rotation(f32::NAN);
+    }
+}
@@ -1,13 +1,33 @@
 pub mod axial;
-//pub mod ideal;
+pub mod chunked;
+pub mod cube;
+pub mod direction;
+mod error;
+pub mod float;
+pub mod geometry;
+pub mod ideal;
+pub mod int;
 pub mod hex;
+pub mod layout;
+pub mod map;
+pub mod offset;
+pub mod pathfinding;
 pub mod pixelhex;
+pub mod raster;
+pub mod region;
+pub mod rotation;
+pub mod tiling;
+pub mod visibility;
 
 use axial::Axial;
+use direction::{HexDiagonal, HexDirection};
+use geometry::CornerId;
+use offset::{DoubledCoord, OffsetCoord, Parity};
+pub use error::Error;
 use serde::{Deserialize, Serialize};
 
 /// Defines how the hexagon is orientated.
-#[derive(Copy, Clone, Debug, Deserialize, Serialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub enum HexTop {
     FLAT,
     POINTY,
@@ -15,6 +35,16 @@ pub enum HexTop {
 
 pub trait Hexagons {
 
+    /// Return how the hexagons are orientated.
+    /// The default compares the y of the hexagons `(0, 0)` and `(1, 0)`, which is the same for pointy tops.
+    fn top(&self) -> HexTop {
+        if self.xy_ref(&Axial::new(1, 0)).1 == self.xy_ref(&Axial::default()).1 {
+            HexTop::POINTY
+        } else {
+            HexTop::FLAT
+        }
+    }
+
     /// Return the spacing in x direction.
     fn horizontal_spacing(&self) -> f32;
 
@@ -30,4 +60,68 @@ pub trait Hexagons {
     /// Compute the axial index of a hexagon from a x,y coordinate.
     fn axial(&self, xy: (f32, f32)) -> Axial;
 
+    /// Compute the x,y reference of a hexagon from any coordinates convertible to Axial.
+    fn xy_ref_of<C: Into<Axial>>(&self, c: C) -> (f32, f32) where Self: Sized {
+        self.xy_ref(&c.into())
+    }
+
+    /// Compute the offset coordinates of a hexagon from a x,y coordinate.
+    fn offset(&self, xy: (f32, f32), parity: Parity) -> OffsetCoord {
+        OffsetCoord::from_axial(self.axial(xy), self.top(), parity)
+    }
+
+    /// Compute the doubled coordinates of a hexagon from a x,y coordinate.
+    fn doubled(&self, xy: (f32, f32)) -> DoubledCoord {
+        DoubledCoord::from_axial(self.axial(xy), self.top())
+    }
+
+    /// Check if the x,y coordinate belongs to the hexagon with Axial coordinates `qr`.
+    /// The default asks `axial`, so every coordinate belongs to exactly one hexagon by construction.
+    /// Implementations with their own notion of the hexagon's shape should override it.
+    fn contains(&self, qr: &Axial, xy: (f32, f32)) -> bool {
+        self.axial(xy) == *qr
+    }
+
+    /// Compute `axial` for every x,y coordinate of `xy` and write the results to `out`.
+    /// Panics if the slices have different lengths.
+    fn axial_batch(&self, xy: &[(f32, f32)], out: &mut [Axial]) {
+        assert_eq!(xy.len(), out.len(), "axial_batch needs slices of the same length");
+        for (qr, xy) in out.iter_mut().zip(xy) {
+            *qr = self.axial(*xy);
+        }
+    }
+
+    /// Compute `xy_ref` for every hexagon of `qr` and write the results to `out`.
+    /// Panics if the slices have different lengths.
+    fn xy_ref_batch(&self, qr: &[Axial], out: &mut [(f32, f32)]) {
+        assert_eq!(qr.len(), out.len(), "xy_ref_batch needs slices of the same length");
+        for (xy, qr) in out.iter_mut().zip(qr) {
+            *xy = self.xy_ref(qr);
+        }
+    }
+
+    /// Compute the x,y position of the corner of `qr` in `diagonal`.
+    /// All three hexagons sharing the corner give exactly the same position.
+    fn corner(&self, qr: &Axial, diagonal: HexDiagonal) -> (f32, f32) {
+        geometry::lattice_corner(self, CornerId::new(*qr, diagonal))
+    }
+
+    /// Compute the outline of `qr`, clockwise with y pointing down.
+    fn corners(&self, qr: &Axial) -> Vec<(f32, f32)> {
+        HexDiagonal::all().iter().map(|diag| self.corner(qr, *diag)).collect()
+    }
+
+    /// Compute the outline between `qr` and its neighbour in `direction`, clockwise around `qr`.
+    /// The neighbour gets the same points in reversed order.
+    fn edge(&self, qr: &Axial, direction: HexDirection) -> Vec<(f32, f32)> {
+        let (a, b) = direction.corners();
+        vec![self.corner(qr, a), self.corner(qr, b)]
+    }
+
+    /// Iterate over all hexagons which could touch the rectangle from `min_xy` to `max_xy`,
+    /// row by row from top to bottom and from left to right within a row.
+    fn hexes_in_viewport(&self, min_xy: (f32, f32), max_xy: (f32, f32)) -> std::vec::IntoIter<Axial> {
+        raster::viewport(self, min_xy, max_xy).into_iter()
+    }
+
 }
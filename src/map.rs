@@ -0,0 +1,264 @@
+//! Dense storage of values for a bounded set of hexagons.
+use std::ops::{Index, IndexMut};
+
+use serde::{Deserialize, Serialize};
+
+use super::axial::Axial;
+use super::HexTop;
+
+/// Shape of the hexagons stored in a `HexMap`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum MapShape {
+    /// All hexagons within `radius` of the origin.
+    Hexagon { radius: u32 },
+    /// All hexagons with q and r in the inclusive ranges.
+    Parallelogram { q: (i32, i32), r: (i32, i32) },
+    /// All hexagons with q and r at least 0 and q + r at most `size`.
+    Triangle { size: u32 },
+    /// `width` times `height` hexagons which fill a rectangle on screen, starting at the origin.
+    Rectangle { top: HexTop, width: u32, height: u32 },
+}
+
+/// A row of hexagons with the same r, given by the first q and the number of hexagons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Row {
+    r: i32,
+    q: i32,
+    len: usize,
+}
+
+impl MapShape {
+    /// Checks if `qr` is part of the shape.
+    pub fn contains(&self, qr: Axial) -> bool {
+        let (q, r) = qr.to_tuple();
+        match *self {
+            MapShape::Hexagon { radius } => qr.length() <= radius,
+            MapShape::Parallelogram { q: (q1, q2), r: (r1, r2) } => q >= q1 && q <= q2 && r >= r1 && r <= r2,
+            MapShape::Triangle { size } => q >= 0 && r >= 0 && q + r <= size as i32,
+            MapShape::Rectangle { top: HexTop::POINTY, width, height } => {
+                let col = q + r.div_euclid(2);
+                r >= 0 && r < height as i32 && col >= 0 && col < width as i32
+            },
+            MapShape::Rectangle { top: HexTop::FLAT, width, height } => {
+                let row = r + q.div_euclid(2);
+                q >= 0 && q < width as i32 && row >= 0 && row < height as i32
+            },
+        }
+    }
+
+    /// The rows of the shape, ordered by r.
+    fn rows(&self) -> Vec<Row> {
+        let row = |r: i32, q1: i32, q2: i32| Row { r, q: q1, len: (q2 - q1 + 1).max(0) as usize };
+        match *self {
+            MapShape::Hexagon { radius } => {
+                let n = radius as i32;
+                (-n..=n).map(|r| row(r, (-n).max(-n - r), n.min(n - r))).collect()
+            },
+            MapShape::Parallelogram { q: (q1, q2), r: (r1, r2) } => {
+                (r1..=r2).map(|r| row(r, q1, q2)).collect()
+            },
+            MapShape::Triangle { size } => {
+                let n = size as i32;
+                (0..=n).map(|r| row(r, 0, n - r)).collect()
+            },
+            MapShape::Rectangle { top: HexTop::POINTY, width, height } => {
+                let w = width as i32;
+                (0..height as i32).map(|r| row(r, -r.div_euclid(2), w - 1 - r.div_euclid(2))).collect()
+            },
+            MapShape::Rectangle { top: HexTop::FLAT, width, height } => {
+                if width == 0 {
+                    return Vec::new();
+                }
+                // the hexagons with q = 2f and q = 2f + 1 are in the same rows
+                let (w, h) = (width as i32, height as i32);
+                let f_last = (w - 1).div_euclid(2);
+                (-f_last..h).map(|r| {
+                    let f1 = (-r).max(0);
+                    let f2 = f_last.min(h - 1 - r);
+                    row(r, 2 * f1, (2 * f2 + 1).min(w - 1))
+                }).collect()
+            },
+        }
+    }
+
+    /// All hexagons of the shape, ordered by r and then by q.
+    pub fn hexes(&self) -> impl Iterator<Item = Axial> {
+        self.rows().into_iter()
+            .flat_map(|row| (0..row.len as i32).map(move |i| Axial::new(row.q + i, row.r)))
+    }
+}
+
+/// Values of type `T` for all hexagons of a `MapShape`.
+#[derive(Debug, Clone)]
+pub struct HexMap<T> {
+    shape: MapShape,
+    rows: Vec<Row>,
+    offsets: Vec<usize>,
+    cells: Vec<T>,
+}
+
+impl<T: Default> HexMap<T> {
+    /// Creates a map with default values.
+    pub fn new(shape: MapShape) -> Self {
+        HexMap::from_fn(shape, |_| T::default())
+    }
+}
+
+impl<T> HexMap<T> {
+    /// Creates a map with the value `f(qr)` for every hexagon `qr`.
+    pub fn from_fn(shape: MapShape, f: impl FnMut(Axial) -> T) -> Self {
+        let rows = shape.rows();
+        let mut offsets = Vec::with_capacity(rows.len());
+        let mut total = 0usize;
+        for row in &rows {
+            offsets.push(total);
+            total += row.len;
+        }
+        let cells: Vec<T> = shape.hexes().map(f).collect();
+        HexMap {
+            shape,
+            rows,
+            offsets,
+            cells,
+        }
+    }
+
+    pub fn shape(&self) -> MapShape {
+        self.shape
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Position of `qr` in `cells`.
+    fn index_of(&self, qr: Axial) -> Option<usize> {
+        let (q, r) = qr.to_tuple();
+        let first = self.rows.first()?;
+        let i = usize::try_from(r as i64 - first.r as i64).ok()?;
+        let row = self.rows.get(i)?;
+        let j = usize::try_from(q as i64 - row.q as i64).ok()?;
+        if j < row.len {
+            Some(self.offsets[i] + j)
+        } else {
+            None
+        }
+    }
+
+    pub fn contains(&self, qr: Axial) -> bool {
+        self.index_of(qr).is_some()
+    }
+
+    pub fn get(&self, qr: Axial) -> Option<&T> {
+        self.index_of(qr).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, qr: Axial) -> Option<&mut T> {
+        self.index_of(qr).map(|i| &mut self.cells[i])
+    }
+
+    /// All hexagons with their values, ordered by r and then by q.
+    pub fn iter(&self) -> impl Iterator<Item = (Axial, &T)> {
+        self.shape.hexes().zip(self.cells.iter())
+    }
+
+    /// All hexagons with mutable values, ordered by r and then by q.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Axial, &mut T)> {
+        self.shape.hexes().zip(self.cells.iter_mut())
+    }
+
+    /// The neighbours of `qr` which are part of the map.
+    pub fn neighbours_of(&self, qr: Axial) -> impl Iterator<Item = (Axial, &T)> {
+        qr.neighbours().filter_map(move |n| self.get(n).map(|v| (n, v)))
+    }
+}
+
+impl<T> Index<Axial> for HexMap<T> {
+    type Output = T;
+
+    fn index(&self, qr: Axial) -> &Self::Output {
+        self.get(qr).expect("hexagon outside of the map")
+    }
+}
+
+impl<T> IndexMut<Axial> for HexMap<T> {
+    fn index_mut(&mut self, qr: Axial) -> &mut Self::Output {
+        self.get_mut(qr).expect("hexagon outside of the map")
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shapes() -> Vec<MapShape> {
+        vec![
+            MapShape::Hexagon { radius: 0 },
+            MapShape::Hexagon { radius: 3 },
+            MapShape::Parallelogram { q: (-2, 3), r: (1, 4) },
+            MapShape::Triangle { size: 4 },
+            MapShape::Rectangle { top: HexTop::POINTY, width: 5, height: 4 },
+            MapShape::Rectangle { top: HexTop::FLAT, width: 5, height: 4 },
+            MapShape::Rectangle { top: HexTop::FLAT, width: 6, height: 1 },
+            MapShape::Rectangle { top: HexTop::FLAT, width: 0, height: 3 },
+        ]
+    }
+
+    #[test]
+    fn test_shapes() {
+        for shape in shapes() {
+            let hexes: Vec<Axial> = shape.hexes().collect();
+            let expected: Vec<Axial> = Axial::default().range(12)
+                .filter(|qr| shape.contains(*qr))
+                .collect();
+            let mut sorted = hexes.clone();
+            sorted.sort_by_key(|qr| qr.to_tuple());
+            assert_eq!(sorted, expected, "{:?}", shape);
+        }
+        assert_eq!(MapShape::Hexagon { radius: 3 }.hexes().count(), 37);
+        assert_eq!(MapShape::Triangle { size: 4 }.hexes().count(), 15);
+        assert_eq!(MapShape::Rectangle { top: HexTop::FLAT, width: 5, height: 4 }.hexes().count(), 20);
+        assert_eq!(MapShape::Rectangle { top: HexTop::POINTY, width: 5, height: 4 }.hexes().count(), 20);
+    }
+
+    #[test]
+    fn test_map() {
+        for shape in shapes() {
+            let mut map = HexMap::from_fn(shape, |qr| qr.to_tuple());
+            assert_eq!(map.len(), shape.hexes().count());
+            for qr in Axial::default().range(12) {
+                assert_eq!(map.contains(qr), shape.contains(qr));
+                assert_eq!(map.get(qr).copied(), Some(qr.to_tuple()).filter(|_| shape.contains(qr)));
+            }
+            for (qr, v) in map.iter_mut() {
+                v.0 += 100;
+                assert_eq!(v.1, qr.to_tuple().1);
+            }
+            for (qr, v) in map.iter() {
+                assert_eq!(*v, (qr.to_tuple().0 + 100, qr.to_tuple().1));
+            }
+        }
+        let mut map: HexMap<u8> = HexMap::new(MapShape::Hexagon { radius: 2 });
+        map[Axial::new(1, -2)] = 7;
+        *map.get_mut(Axial::new(-2, 0)).unwrap() = 3;
+        assert_eq!(map[Axial::new(1, -2)], 7);
+        assert_eq!(map.get(Axial::new(-2, 0)), Some(&3));
+        assert_eq!(map.get(Axial::new(3, 0)), None);
+        assert_eq!(map.get(Axial::new(i32::MIN, i32::MAX)), None);
+    }
+
+    #[test]
+    fn test_neighbours_of() {
+        let map: HexMap<u8> = HexMap::new(MapShape::Triangle { size: 3 });
+        assert_eq!(map.neighbours_of(Axial::new(1, 1)).count(), 6);
+        let corner: Vec<Axial> = map.neighbours_of(Axial::default()).map(|(qr, _)| qr).collect();
+        assert_eq!(corner, vec![Axial::new(0, 1), Axial::new(1, 0)]);
+        assert_eq!(map.neighbours_of(Axial::new(3, 0)).count(), 2);
+        assert_eq!(map.neighbours_of(Axial::new(-5, 0)).count(), 0);
+    }
+}
@@ -0,0 +1,277 @@
+//! Offset and doubled coordinates as used by tile editors and file formats.
+//! Following the great page https://www.redblobgames.com/grids/hexagons/
+use super::axial::Axial;
+use super::direction::HexDirection;
+use super::HexTop;
+
+/// Which rows (pointy top) or columns (flat top) are shifted by half a hexagon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Parity {
+    Even,
+    Odd,
+}
+
+/// Offset coordinates. Pointy top hexagons shift every other row,
+/// flat top hexagons shift every other column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OffsetCoord {
+    pub col: i32,
+    pub row: i32,
+    pub top: HexTop,
+    pub parity: Parity,
+}
+
+/// Doubled coordinates. Pointy top hexagons double the columns,
+/// flat top hexagons double the rows, so `col + row` is always even.
+/// The doubled line does not fit into `i32` for every `Axial`, so `col` and `row` are `i64`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DoubledCoord {
+    qr: Axial,
+    top: HexTop,
+}
+
+/// Half of the shift of line `v`.
+fn shift(v: i32, parity: Parity) -> i32 {
+    match parity {
+        Parity::Odd => (v - (v & 1)) / 2,
+        Parity::Even => (v + (v & 1)) / 2,
+    }
+}
+
+impl OffsetCoord {
+    pub fn new(col: i32, row: i32, top: HexTop, parity: Parity) -> Self {
+        OffsetCoord { col, row, top, parity }
+    }
+
+    /// Converts `qr` into offset coordinates with the given layout.
+    pub fn from_axial(qr: Axial, top: HexTop, parity: Parity) -> Self {
+        let (q, r) = qr.to_tuple();
+        match top {
+            HexTop::POINTY => OffsetCoord::new(q + shift(r, parity), r, top, parity),
+            HexTop::FLAT => OffsetCoord::new(q, r + shift(q, parity), top, parity),
+        }
+    }
+
+    pub fn to_axial(&self) -> Axial {
+        match self.top {
+            HexTop::POINTY => Axial::new(self.col - shift(self.row, self.parity), self.row),
+            HexTop::FLAT => Axial::new(self.col, self.row - shift(self.col, self.parity)),
+        }
+    }
+
+    pub fn neighbour(&self, dir: HexDirection) -> Self {
+        OffsetCoord::from_axial(self.to_axial().neighbour(dir), self.top, self.parity)
+    }
+
+    /// The six neighbours in the order of `HexDirection::all`.
+    pub fn neighbours(&self) -> [Self; 6] {
+        HexDirection::all().map(|dir| self.neighbour(dir))
+    }
+
+    pub fn distance_to(&self, other: Self) -> u32 {
+        self.to_axial().distance_to(other.to_axial())
+    }
+}
+
+impl DoubledCoord {
+    /// Panics if `col + row` is odd or the hexagon is outside of the `Axial` range, see `try_new`.
+    pub fn new(col: impl Into<i64>, row: impl Into<i64>, top: HexTop) -> Self {
+        let (col, row) = (col.into(), row.into());
+        DoubledCoord::try_new(col, row, top)
+            .unwrap_or_else(|| panic!("doubled coordinates ({}, {}) are not a hexagon", col, row))
+    }
+
+    /// Returns `None` if `col + row` is odd, which is not a hexagon,
+    /// or if the hexagon does not fit into `Axial`.
+    pub fn try_new(col: impl Into<i64>, row: impl Into<i64>, top: HexTop) -> Option<Self> {
+        let (col, row) = (col.into(), row.into());
+        if (col ^ row) & 1 != 0 {
+            return None;
+        }
+        let (q, r) = match top {
+            HexTop::POINTY => (col.checked_sub(row)? / 2, row),
+            HexTop::FLAT => (col, row.checked_sub(col)? / 2),
+        };
+        let qr = Axial::new(i32::try_from(q).ok()?, i32::try_from(r).ok()?);
+        Some(DoubledCoord { qr, top })
+    }
+
+    /// Converts `qr` into doubled coordinates with the given layout, which never overflows.
+    pub fn from_axial(qr: Axial, top: HexTop) -> Self {
+        DoubledCoord { qr, top }
+    }
+
+    pub fn col(&self) -> i64 {
+        let (q, r) = self.qr.to_tuple();
+        match self.top {
+            HexTop::POINTY => 2 * q as i64 + r as i64,
+            HexTop::FLAT => q as i64,
+        }
+    }
+
+    pub fn row(&self) -> i64 {
+        let (q, r) = self.qr.to_tuple();
+        match self.top {
+            HexTop::POINTY => r as i64,
+            HexTop::FLAT => 2 * r as i64 + q as i64,
+        }
+    }
+
+    pub fn top(&self) -> HexTop {
+        self.top
+    }
+
+    pub fn to_axial(&self) -> Axial {
+        self.qr
+    }
+
+    pub fn neighbour(&self, dir: HexDirection) -> Self {
+        DoubledCoord::from_axial(self.to_axial().neighbour(dir), self.top)
+    }
+
+    /// The six neighbours in the order of `HexDirection::all`.
+    pub fn neighbours(&self) -> [Self; 6] {
+        HexDirection::all().map(|dir| self.neighbour(dir))
+    }
+
+    /// Saturates like `Axial::distance_to`.
+    pub fn distance_to(&self, other: Self) -> u32 {
+        let dcol = self.col().abs_diff(other.col());
+        let drow = self.row().abs_diff(other.row());
+        let d = match self.top {
+            HexTop::POINTY => drow + dcol.saturating_sub(drow) / 2,
+            HexTop::FLAT => dcol + drow.saturating_sub(dcol) / 2,
+        };
+        u32::try_from(d).unwrap_or(u32::MAX)
+    }
+}
+
+impl From<OffsetCoord> for Axial {
+    fn from(c: OffsetCoord) -> Self {
+        c.to_axial()
+    }
+}
+
+impl From<DoubledCoord> for Axial {
+    fn from(c: DoubledCoord) -> Self {
+        c.to_axial()
+    }
+}
+
+impl From<OffsetCoord> for DoubledCoord {
+    fn from(c: OffsetCoord) -> Self {
+        DoubledCoord::from_axial(c.to_axial(), c.top)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex::Props;
+    use crate::Hexagons;
+
+    #[test]
+    fn test_offset() {
+        // examples from redblobgames
+        let qr = Axial::new(-1, 3);
+        assert_eq!(OffsetCoord::from_axial(qr, HexTop::POINTY, Parity::Odd), OffsetCoord::new(0, 3, HexTop::POINTY, Parity::Odd));
+        assert_eq!(OffsetCoord::from_axial(qr, HexTop::POINTY, Parity::Even), OffsetCoord::new(1, 3, HexTop::POINTY, Parity::Even));
+        assert_eq!(OffsetCoord::from_axial(qr, HexTop::FLAT, Parity::Odd), OffsetCoord::new(-1, 2, HexTop::FLAT, Parity::Odd));
+        assert_eq!(OffsetCoord::from_axial(qr, HexTop::FLAT, Parity::Even), OffsetCoord::new(-1, 3, HexTop::FLAT, Parity::Even));
+        for qr in Axial::new(-3, 2).range(8) {
+            for top in [HexTop::FLAT, HexTop::POINTY] {
+                for parity in [Parity::Even, Parity::Odd] {
+                    let c = OffsetCoord::from_axial(qr, top, parity);
+                    assert_eq!(Axial::from(c), qr);
+                    for (dir, n) in HexDirection::all().iter().zip(c.neighbours()) {
+                        assert_eq!(n.to_axial(), qr.neighbour(*dir));
+                        assert_eq!(c.distance_to(n), 1);
+                    }
+                    assert_eq!(c.distance_to(OffsetCoord::from_axial(Axial::default(), top, parity)), qr.length());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_doubled() {
+        let qr = Axial::new(-1, 3);
+        assert_eq!(DoubledCoord::from_axial(qr, HexTop::POINTY), DoubledCoord::new(1, 3, HexTop::POINTY));
+        assert_eq!(DoubledCoord::from_axial(qr, HexTop::FLAT), DoubledCoord::new(-1, 5, HexTop::FLAT));
+        let origin = Axial::new(2, -5);
+        for qr in origin.range(8) {
+            for top in [HexTop::FLAT, HexTop::POINTY] {
+                let c = DoubledCoord::from_axial(qr, top);
+                assert_eq!((c.col() + c.row()).rem_euclid(2), 0);
+                assert_eq!(Axial::from(c), qr);
+                assert_eq!(c.distance_to(DoubledCoord::from_axial(origin, top)), qr.distance_to(origin));
+                for (dir, n) in HexDirection::all().iter().zip(c.neighbours()) {
+                    assert_eq!(n.to_axial(), qr.neighbour(*dir));
+                }
+                let o = OffsetCoord::from_axial(qr, top, Parity::Odd);
+                assert_eq!(DoubledCoord::from(o), c);
+            }
+        }
+        assert_eq!(DoubledCoord::try_new(-3, 5, HexTop::FLAT), Some(DoubledCoord::new(-3, 5, HexTop::FLAT)));
+        assert_eq!(DoubledCoord::try_new(2, -1, HexTop::POINTY), None);
+        assert_eq!(DoubledCoord::new(-3, 5, HexTop::FLAT).top(), HexTop::FLAT);
+    }
+
+    #[test]
+    fn test_doubled_limits() {
+        for qr in [Axial::new(i32::MAX, i32::MAX), Axial::new(i32::MIN, i32::MIN), Axial::new(i32::MAX, i32::MIN)] {
+            for top in [HexTop::FLAT, HexTop::POINTY] {
+                let c = DoubledCoord::from_axial(qr, top);
+                assert_eq!(c.to_axial(), qr);
+                assert_eq!(DoubledCoord::try_new(c.col(), c.row(), top), Some(c));
+            }
+        }
+        let c = DoubledCoord::from_axial(Axial::new(i32::MAX, 0), HexTop::POINTY);
+        assert_eq!(c.col(), 2 * i32::MAX as i64);
+        assert_eq!(DoubledCoord::try_new(c.col() + 2, 0, HexTop::POINTY), None);
+        assert_eq!(DoubledCoord::try_new(i64::MAX - 1, i64::MIN, HexTop::POINTY), None);
+        let far = DoubledCoord::from_axial(Axial::new(i32::MIN, 0), HexTop::POINTY);
+        assert_eq!(c.distance_to(far), u32::MAX);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_doubled_parity() {
+        DoubledCoord::new(1, 0, HexTop::FLAT);
+    }
+
+    #[test]
+    fn test_hexagons() {
+        let h = Props::pointy(10f32, 8f32);
+        let c = OffsetCoord::new(2, 3, HexTop::POINTY, Parity::Odd);
+        let xy = h.xy_ref_of(c);
+        assert_eq!(xy, h.xy_ref(&c.to_axial()));
+        assert_eq!(h.offset(xy, Parity::Odd), c);
+        assert_eq!(h.doubled(xy), DoubledCoord::from(c));
+        assert_eq!(h.xy_ref_of(DoubledCoord::from(c)), xy);
+        assert_eq!(h.xy_ref_of(Axial::new(1, 1)), (15f32, 8f32));
+    }
+
+    /// Implements only the required methods.
+    struct Plain(Props);
+
+    impl Hexagons for Plain {
+        fn horizontal_spacing(&self) -> f32 { self.0.horizontal_spacing() }
+        fn vertical_spacing(&self) -> f32 { self.0.vertical_spacing() }
+        fn xy_ref(&self, qr: &Axial) -> (f32, f32) { Hexagons::xy_ref(&self.0, qr) }
+        fn xy_relative(&self, xy: (f32, f32)) -> (f32, f32) { self.0.xy_relative(xy) }
+        fn axial(&self, xy: (f32, f32)) -> Axial { Hexagons::axial(&self.0, xy) }
+    }
+
+    #[test]
+    fn test_default_top() {
+        for p in [Props::flat(7f32, 10f32), Props::pointy(10f32, 8f32)] {
+            let top = p.top();
+            let plain = Plain(p);
+            assert_eq!(plain.top(), top);
+            let xy = plain.xy_ref(&Axial::new(-2, 3));
+            assert_eq!(plain.doubled(xy), DoubledCoord::from_axial(Axial::new(-2, 3), top));
+        }
+    }
+}
@@ -0,0 +1,389 @@
+//! Shortest paths over hexagons.
+//!
+//! The cost of a step comes from a callback `(from, to) -> Option<u32>`, where `None`
+//! means the step is impossible. This allows walls, map borders and directional costs.
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use super::axial::Axial;
+
+/// Limits and debugging options of a search.
+/// On an unbounded grid the search only ends for unreachable goals if a limit is set.
+#[derive(Debug, Clone, Copy)]
+pub struct SearchOptions {
+    /// Hexagons which cost more to reach are not expanded.
+    pub max_cost: Option<u32>,
+    /// The search stops after expanding that many hexagons.
+    pub max_explored: Option<usize>,
+    /// Smallest cost of a step, the A* heuristic is `distance_to` times this.
+    /// If a step is cheaper, A* might not find the cheapest path.
+    pub min_step_cost: u32,
+    /// Keep the set of expanded hexagons in the result.
+    pub record_explored: bool,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        SearchOptions {
+            max_cost: None,
+            max_explored: None,
+            min_step_cost: 1,
+            record_explored: false,
+        }
+    }
+}
+
+/// A path found by a search.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathResult {
+    /// The hexagons from start to goal, both included.
+    pub path: Vec<Axial>,
+    /// The sum of the step costs along the path.
+    pub cost: u32,
+    /// The expanded hexagons, if `record_explored` was set.
+    pub explored: Option<HashSet<Axial>>,
+}
+
+/// Cheapest costs from a start hexagon, as computed by `dijkstra`.
+/// Only expanded hexagons are kept, so all costs are final even if a limit stopped the search.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CostMap {
+    /// The cost to reach every found hexagon.
+    pub costs: HashMap<Axial, u32>,
+    /// The previous hexagon on the cheapest path, for all but the start.
+    pub came_from: HashMap<Axial, Axial>,
+}
+
+impl CostMap {
+    pub fn cost(&self, qr: Axial) -> Option<u32> {
+        self.costs.get(&qr).copied()
+    }
+
+    /// The previous hexagon on the cheapest path to `qr`.
+    pub fn predecessor(&self, qr: Axial) -> Option<Axial> {
+        self.came_from.get(&qr).copied()
+    }
+
+    pub fn contains(&self, qr: Axial) -> bool {
+        self.costs.contains_key(&qr)
+    }
+
+    /// The cheapest path from the start to `goal`, both included.
+    pub fn path_to(&self, goal: Axial) -> Option<Vec<Axial>> {
+        if !self.costs.contains_key(&goal) {
+            return None;
+        }
+        let mut path = vec![goal];
+        let mut current = goal;
+        while let Some(prev) = self.came_from.get(&current) {
+            path.push(*prev);
+            current = *prev;
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// Entry of the open list, ordered so that the `BinaryHeap` pops the lowest priority first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Node {
+    priority: u32,
+    cost: u32,
+    qr: Axial,
+}
+
+impl Ord for Node {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // on equal priority prefer the node which is further from the start
+        other.priority.cmp(&self.priority)
+            .then(self.cost.cmp(&other.cost))
+    }
+}
+
+impl PartialOrd for Node {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Best first search from `start`, stopping at `goal` if given.
+/// Hexagons for which `stops` is true are entered with at least `max_cost`, but never left.
+/// The returned `CostMap` only holds the hexagons which have been taken from the open list.
+fn search<F, S>(start: Axial, goal: Option<Axial>, mut cost: F, mut stops: S, options: &SearchOptions) -> (CostMap, HashSet<Axial>)
+where
+    F: FnMut(Axial, Axial) -> Option<u32>,
+    S: FnMut(Axial) -> bool,
+{
+    let heuristic = |qr: Axial| goal.map_or(0, |g| qr.distance_to(g).saturating_mul(options.min_step_cost));
+    let mut map = CostMap::default();
+    let mut explored = HashSet::new();
+    let mut open = BinaryHeap::new();
+    map.costs.insert(start, 0);
+    open.push(Node { priority: heuristic(start), cost: 0, qr: start });
+
+    while let Some(Node { cost: current_cost, qr: current, .. }) = open.pop() {
+        if map.costs.get(&current).is_some_and(|c| *c < current_cost) || explored.contains(&current) {
+            continue;
+        }
+        explored.insert(current);
+        if Some(current) == goal || options.max_explored.is_some_and(|m| explored.len() >= m) {
+            break;
+        }
+        if current != start && stops(current) {
+            continue;
+        }
+        for next in current.neighbours() {
+            let Some(step) = cost(current, next) else {
+                continue;
+            };
+            let mut next_cost = current_cost.saturating_add(step);
+            if options.max_cost.is_some_and(|m| next_cost > m) {
+                continue;
+            }
+            if let Some(max_cost) = options.max_cost.filter(|_| stops(next)) {
+                next_cost = max_cost;
+            }
+            if map.costs.get(&next).is_none_or(|c| next_cost < *c) {
+                map.costs.insert(next, next_cost);
+                map.came_from.insert(next, current);
+                open.push(Node { priority: next_cost.saturating_add(heuristic(next)), cost: next_cost, qr: next });
+            }
+        }
+    }
+    // costs of the hexagons still in the open list might not be the cheapest
+    map.costs.retain(|qr, _| explored.contains(qr));
+    map.came_from.retain(|qr, _| explored.contains(qr));
+    (map, explored)
+}
+
+/// The path to `goal`, if the search reached it before a limit stopped it.
+fn path_result(map: &CostMap, goal: Axial, explored: HashSet<Axial>, options: &SearchOptions) -> Option<PathResult> {
+    let path = map.path_to(goal)?;
+    Some(PathResult {
+        path,
+        cost: map.costs[&goal],
+        explored: options.record_explored.then_some(explored),
+    })
+}
+
+/// Cheapest path from `start` to `goal` using A* with `distance_to` as heuristic.
+pub fn astar<F>(start: Axial, goal: Axial, cost: F, options: &SearchOptions) -> Option<PathResult>
+where
+    F: FnMut(Axial, Axial) -> Option<u32>,
+{
+    let (map, explored) = search(start, Some(goal), cost, |_| false, options);
+    path_result(&map, goal, explored, options)
+}
+
+/// Cheapest path from `start` to `goal`, expanding hexagons in the order of their cost.
+pub fn uniform_cost<F>(start: Axial, goal: Axial, cost: F, options: &SearchOptions) -> Option<PathResult>
+where
+    F: FnMut(Axial, Axial) -> Option<u32>,
+{
+    let options = SearchOptions { min_step_cost: 0, ..*options };
+    let (map, explored) = search(start, Some(goal), cost, |_| false, &options);
+    path_result(&map, goal, explored, &options)
+}
+
+/// Cheapest costs from `start` to all hexagons within the limits of `options`.
+/// If `max_explored` stops the search, only the expanded hexagons are returned.
+pub fn dijkstra<F>(start: Axial, cost: F, options: &SearchOptions) -> CostMap
+where
+    F: FnMut(Axial, Axial) -> Option<u32>,
+{
+    search(start, None, cost, |_| false, options).0
+}
+
+/// All hexagons which can be reached from `start` with movement points `budget`,
+/// with the cheapest cost and path to each of them.
+pub fn reachable<F>(start: Axial, budget: u32, cost: F) -> CostMap
+where
+    F: FnMut(Axial, Axial) -> Option<u32>,
+{
+    reachable_with_zoc(start, budget, cost, |_| false)
+}
+
+/// Like `reachable`, but entering a hexagon in a zone of control uses up the remaining budget.
+/// Such hexagons are reported with cost `budget` and the movement ends there.
+/// Leaving the start hexagon is always possible.
+pub fn reachable_with_zoc<F, Z>(start: Axial, budget: u32, cost: F, zone_of_control: Z) -> CostMap
+where
+    F: FnMut(Axial, Axial) -> Option<u32>,
+    Z: FnMut(Axial) -> bool,
+{
+    let options = SearchOptions { max_cost: Some(budget), ..SearchOptions::default() };
+    search(start, None, cost, zone_of_control, &options).0
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::{HexMap, MapShape};
+
+    /// Walls along q = 2 except at r = -3.
+    fn walls(_from: Axial, to: Axial) -> Option<u32> {
+        let (q, r) = to.to_tuple();
+        if to.length() > 5 || (q == 2 && r != -3) {
+            None
+        } else {
+            Some(1)
+        }
+    }
+
+    #[test]
+    fn test_open() {
+        let start = Axial::new(-2, 1);
+        let goal = Axial::new(3, -1);
+        let result = astar(start, goal, |_, _| Some(1), &SearchOptions::default()).unwrap();
+        assert_eq!(result.cost, start.distance_to(goal));
+        assert_eq!(result.path.len(), 6);
+        assert_eq!(result.path.first(), Some(&start));
+        assert_eq!(result.path.last(), Some(&goal));
+        assert!(result.explored.is_none());
+
+        let result = astar(start, start, |_, _| Some(1), &SearchOptions::default()).unwrap();
+        assert_eq!(result.path, vec![start]);
+        assert_eq!(result.cost, 0);
+    }
+
+    #[test]
+    fn test_walls() {
+        let start = Axial::new(0, 0);
+        let goal = Axial::new(4, 0);
+        let options = SearchOptions { record_explored: true, ..SearchOptions::default() };
+        let a = astar(start, goal, walls, &options).unwrap();
+        let u = uniform_cost(start, goal, walls, &options).unwrap();
+        assert_eq!(a.cost, u.cost);
+        assert!(a.path.contains(&Axial::new(2, -3)));
+        for w in a.path.windows(2) {
+            assert_eq!(w[0].distance_to(w[1]), 1);
+            assert!(walls(w[0], w[1]).is_some());
+        }
+        assert!(a.explored.unwrap().len() <= u.explored.unwrap().len());
+
+        // goal enclosed by the border
+        assert!(astar(start, Axial::new(7, 0), walls, &SearchOptions::default()).is_none());
+        let limited = SearchOptions { max_cost: Some(4), ..SearchOptions::default() };
+        assert!(astar(start, goal, |_, _| Some(1), &limited).is_some());
+        assert!(astar(start, goal, walls, &limited).is_none());
+    }
+
+    #[test]
+    fn test_max_explored() {
+        // a direct step to the goal costing 10 and a detour of two steps costing 1
+        let (start, middle, goal) = (Axial::default(), Axial::new(0, 1), Axial::new(1, 0));
+        let cost = |from: Axial, to: Axial| match (from, to) {
+            (f, t) if f == start && t == goal => Some(10),
+            (f, t) if f == start && t == middle => Some(1),
+            (f, t) if f == middle && t == goal => Some(1),
+            _ => None,
+        };
+        let result = astar(start, goal, cost, &SearchOptions::default()).unwrap();
+        assert_eq!((result.path, result.cost), (vec![start, middle, goal], 2));
+
+        // the goal is only in the open list when the search stops
+        let limited = SearchOptions { max_explored: Some(2), ..SearchOptions::default() };
+        assert!(astar(start, goal, cost, &limited).is_none());
+        assert!(uniform_cost(start, goal, cost, &limited).is_none());
+        let map = dijkstra(start, cost, &limited);
+        assert_eq!(map.cost(middle), Some(1));
+        assert!(!map.contains(goal));
+        assert_eq!(dijkstra(start, cost, &SearchOptions::default()).cost(goal), Some(2));
+    }
+
+    #[test]
+    fn test_directional() {
+        // going towards +q is expensive, going back is cheap
+        let cost = |from: Axial, to: Axial| {
+            if to.length() > 4 {
+                None
+            } else if (to - from).to_tuple().0 > 0 {
+                Some(5)
+            } else {
+                Some(1)
+            }
+        };
+        let there = astar(Axial::new(-2, 0), Axial::new(2, 0), cost, &SearchOptions::default()).unwrap();
+        let back = astar(Axial::new(2, 0), Axial::new(-2, 0), cost, &SearchOptions::default()).unwrap();
+        assert_eq!(there.cost, 20);
+        assert_eq!(back.cost, 4);
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        let mut terrain: HexMap<u32> = HexMap::from_fn(MapShape::Hexagon { radius: 4 }, |_| 1);
+        terrain[Axial::new(1, 0)] = 10;
+        let cost = |_from: Axial, to: Axial| terrain.get(to).copied();
+        let map = dijkstra(Axial::default(), cost, &SearchOptions::default());
+        assert_eq!(map.costs.len(), 61);
+        assert_eq!(map.cost(Axial::default()), Some(0));
+        assert_eq!(map.cost(Axial::new(1, 0)), Some(10));
+        assert_eq!(map.cost(Axial::new(4, 0)), Some(5));
+        assert_eq!(map.path_to(Axial::default()), Some(vec![Axial::default()]));
+        let path = map.path_to(Axial::new(2, 0)).unwrap();
+        assert!(!path.contains(&Axial::new(1, 0)));
+        assert_eq!(path.len(), 4);
+        assert_eq!(map.path_to(Axial::new(5, 0)), None);
+
+        let limited = dijkstra(Axial::default(), cost, &SearchOptions { max_cost: Some(2), ..SearchOptions::default() });
+        assert_eq!(limited.costs.len(), 17);
+        let result = uniform_cost(Axial::default(), Axial::new(4, 0), cost, &SearchOptions::default()).unwrap();
+        assert_eq!(result.cost, map.cost(Axial::new(4, 0)).unwrap());
+    }
+
+    #[test]
+    fn test_reachable() {
+        // forest costs 2, water is impassable
+        let terrain = |qr: Axial| match qr.to_tuple() {
+            (1, _) => Some(2),
+            (-1, r) if r >= 0 => None,
+            _ => Some(1),
+        };
+        let cost = |_from: Axial, to: Axial| terrain(to);
+        let start = Axial::new(0, 0);
+        let map = reachable(start, 3, cost);
+        assert_eq!(map.cost(start), Some(0));
+        assert_eq!(map.predecessor(start), None);
+        assert_eq!(map.cost(Axial::new(1, 0)), Some(2));
+        assert_eq!(map.cost(Axial::new(2, 0)), Some(3));
+        assert_eq!(map.predecessor(Axial::new(2, 0)), Some(Axial::new(1, 0)));
+        assert!(!map.contains(Axial::new(-1, 0)));
+        assert!(!map.contains(Axial::new(3, 0)));
+        for (qr, c) in &map.costs {
+            assert!(*c <= 3);
+            let path = map.path_to(*qr).unwrap();
+            let total: u32 = path.windows(2).map(|w| cost(w[0], w[1]).unwrap()).sum();
+            assert_eq!(total, *c);
+        }
+        assert_eq!(reachable(start, 0, cost).costs.len(), 1);
+        assert_eq!(reachable(start, 2, |_, _| Some(1)).costs.len(), 19);
+    }
+
+    #[test]
+    fn test_zone_of_control() {
+        let start = Axial::new(0, 0);
+        let enemy = Axial::new(2, 0);
+        let zoc = |qr: Axial| qr.distance_to(enemy) <= 1;
+        let map = reachable_with_zoc(start, 4, |_, to| (to != enemy).then_some(1), zoc);
+        // entering the zone ends the movement
+        assert_eq!(map.cost(Axial::new(1, 0)), Some(4));
+        assert_eq!(map.cost(Axial::new(3, -1)), Some(4));
+        assert_eq!(map.predecessor(Axial::new(3, -1)), Some(Axial::new(3, -2)));
+        assert!(!map.contains(enemy));
+        assert_eq!(map.cost(Axial::new(-4, 0)), Some(4));
+        // going around the zone is still possible
+        assert_eq!(map.cost(Axial::new(2, -2)), Some(2));
+        assert!(map.contains(Axial::new(4, -2)));
+        for qr in map.costs.keys() {
+            if let Some(prev) = map.predecessor(*qr) {
+                assert!(prev == start || !zoc(prev));
+            }
+        }
+        // starting inside a zone of control
+        let map = reachable_with_zoc(Axial::new(1, 0), 2, |_, _| Some(1), zoc);
+        assert_eq!(map.cost(Axial::new(0, 0)), Some(1));
+        assert_eq!(map.cost(Axial::new(2, -1)), Some(2));
+        assert!(!map.contains(Axial::new(3, -1)));
+    }
+}
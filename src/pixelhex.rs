@@ -1,34 +1,193 @@
 use serde::{Serialize, Deserialize};
 
 use super::axial::Axial;
-use super::Hexagons;
+use super::direction::{HexDiagonal, HexDirection};
+use super::geometry::{lattice_corner, CornerId, EdgeId};
+use super::{Error, HexTop, Hexagons};
 use super::hex::Props;
 
 
+/// A hexagon described pixel by pixel.
+///
+/// Flat top hexagons are described by rows: `vert_extends` is the y range of the rows and
+/// `horiz_extends` holds the x range of every row.
+/// Pointy top hexagons are described by columns: `col_range` is the x range of the columns
+/// and `col_extends` holds the y range of every column.
+///
+/// Deserialized hexagons are checked like the ones built by `try_flat` and `try_pointy`.
 #[derive(Debug, Deserialize, Serialize)]
+#[serde(try_from = "PixelHexData")]
 pub struct PixelHex {
     props: Props,
     vert_extends: (f32, f32),
     horiz_extends: Vec<(f32, f32)>,
+    col_range: (f32, f32),
+    col_extends: Vec<(f32, f32)>,
+    #[serde(skip)]
+    outline: Outline,
+}
+
+/// Pixel outline of the hexagon `(0, 0)`, computed once when the hexagon is built.
+#[derive(Debug, Clone, Default)]
+struct Outline {
+    /// One point per pixel step, see `PixelHex::unit_outline`.
+    points: Vec<(f32, f32)>,
+    /// Index into `points` of the corner in every diagonal, see `PixelHex::corner_indices`.
+    corners: [Option<usize>; 6],
+    /// Indices of the points which are corners or where the outline changes its direction.
+    vertices: Vec<usize>,
+}
+
+/// Unchecked `PixelHex` as it is deserialized.
+#[derive(Deserialize)]
+struct PixelHexData {
+    props: Props,
+    #[serde(default)]
+    vert_extends: (f32, f32),
+    #[serde(default)]
+    horiz_extends: Vec<(f32, f32)>,
+    #[serde(default)]
+    col_range: (f32, f32),
+    #[serde(default)]
+    col_extends: Vec<(f32, f32)>,
+}
+
+impl TryFrom<PixelHexData> for PixelHex {
+    type Error = Error;
+
+    fn try_from(data: PixelHexData) -> Result<Self, Self::Error> {
+        let h = PixelHex {
+            props: data.props,
+            vert_extends: data.vert_extends,
+            horiz_extends: data.horiz_extends,
+            col_range: data.col_range,
+            col_extends: data.col_extends,
+            outline: Outline::default(),
+        };
+        h.check()?;
+        Ok(h.with_outline())
+    }
+}
+
+/// Check the lines `(v, (a1, a2))` given to `try_flat` or `try_pointy`.
+fn check_lines(extends: &[(f32, (f32, f32))]) -> Result<(), Error> {
+    if extends.is_empty() {
+        return Err(Error::Empty);
+    }
+    for (i, (v, (a1, a2))) in extends.iter().enumerate() {
+        if !v.is_finite() || !a1.is_finite() || !a2.is_finite() {
+            return Err(Error::NotFinite);
+        }
+        if i > 0 && *v != extends[i - 1].0 + 1f32 {
+            return Err(Error::NotContiguous(i));
+        }
+        if a1 > a2 {
+            return Err(Error::Inverted(i));
+        }
+    }
+    Ok(())
+}
+
+/// Run of pixels `(line, (first, last))` in a mask.
+type Run = (usize, (usize, usize));
+
+/// Find the run of pixels in each non-empty line of a mask.
+fn mask_runs(lines: usize, len: usize, opaque: impl Fn(usize, usize) -> bool) -> Result<Vec<Run>, Error> {
+    let mut runs: Vec<Run> = Vec::new();
+    for l in 0..lines {
+        let first = (0..len).find(|&i| opaque(l, i));
+        if let Some(first) = first {
+            let last = (first..len).take_while(|&i| opaque(l, i)).last().unwrap();
+            let follows = runs.last().is_none_or(|(prev, _)| *prev + 1 == l);
+            if !follows || (last+1..len).any(|i| opaque(l, i)) {
+                return Err(Error::NotContiguous(l));
+            }
+            runs.push((l, (first, last)));
+        }
+    }
+    Ok(runs)
+}
+
+/// Range covered by the lines of a hexagon, always including 0.
+fn line_range(extends: &[(f32, (f32, f32))]) -> (f32, f32) {
+    extends.iter().map(|(v, _ext)| v)
+        .fold((0f32, 0f32), |acc, &v| {
+            let vmin = if v < acc.0 {
+                v
+            } else {
+                acc.0
+            };
+            let vmax = if v > acc.1 {
+                v
+            } else {
+                acc.1
+            };
+            (vmin, vmax)
+        })
+}
+
+/// Step needed to get `d` back into `(lo, hi)`.
+fn step(d: f32, (lo, hi): (f32, f32)) -> i32 {
+    -((d < lo) as i32) + (d > hi) as i32
+}
+
+/// Extend of the line at `v` if there is one.
+fn line(extends: &[(f32, f32)], start: f32, v: f32) -> Option<(f32, f32)> {
+    let i = (v - start).floor();
+    if i >= 0f32 && i < extends.len() as f32 {
+        Some(extends[i as usize])
+    } else {
+        None
+    }
+}
+
+/// Outline of the lines `extends` starting at `start`, as `(pos, line)` points one pixel apart.
+/// Goes along the first line, then along the line ends and back along the line starts.
+fn line_outline(extends: &[(f32, f32)], start: f32) -> Vec<(f32, f32)> {
+    let mut keys = Vec::new();
+    if let Some(first) = extends.first() {
+        keys.push((first.0 - 0.5f32, start - 0.5f32));
+    }
+    for (i, (_a1, a2)) in extends.iter().enumerate() {
+        let v = start + i as f32;
+        keys.push((a2 + 0.5f32, v - 0.5f32));
+        keys.push((a2 + 0.5f32, v + 0.5f32));
+    }
+    for (i, (a1, _a2)) in extends.iter().enumerate().rev() {
+        let v = start + i as f32;
+        keys.push((a1 - 0.5f32, v + 0.5f32));
+        keys.push((a1 - 0.5f32, v - 0.5f32));
+    }
+    let mut points: Vec<(f32, f32)> = keys.first().copied().into_iter().collect();
+    for (x, y) in keys.into_iter().skip(1) {
+        let (x0, y0) = *points.last().unwrap();
+        let n = (x - x0).abs().max((y - y0).abs()).round() as usize;
+        for k in 1..=n {
+            let t = k as f32 / n as f32;
+            points.push((x0 + (x - x0) * t, y0 + (y - y0) * t));
+        }
+    }
+    // the outline ends where it started
+    points.pop();
+    points
+}
+
+/// Returns true if `b` is on the straight line from `a` to `c`.
+fn collinear(a: (f32, f32), b: (f32, f32), c: (f32, f32)) -> bool {
+    (b.0 - a.0) * (c.1 - a.1) == (b.1 - a.1) * (c.0 - a.0)
+}
+
+/// Moves `qr` without overflowing far away from the origin.
+fn shift(qr: Axial, dq: i32, dr: i32) -> Axial {
+    let (q, r) = qr.to_tuple();
+    Axial::new(q.saturating_add(dq), r.saturating_add(dr))
 }
 
 impl PixelHex {
+    /// Creates a flat top `PixelHex` from rows given as `(y, (x1, x2))`.
     pub fn flat(horiz_extends: &[(f32, (f32, f32))]) -> Self {
 
-        let yext = horiz_extends.iter().map(|(y, _xx)| y)
-            .fold((0f32, 0f32), |acc, &y| {
-                let ymin = if y < acc.0 {
-                    y
-                } else {
-                    acc.0
-                };
-                let ymax = if y > acc.1 {
-                    y
-                } else {
-                    acc.1
-                };
-                (ymin, ymax)
-            });
+        let yext = line_range(horiz_extends);
 
         let minx = horiz_extends.iter()
             .map(|(_y, (x1, _x2))| *x1)
@@ -40,21 +199,302 @@ impl PixelHex {
             PixelHex{
                 props: Props::flat(hs, vs),
                 horiz_extends: horiz_extends.iter().map(|(_y, x_ext)| *x_ext).collect(),
-                vert_extends: yext
-            }
+                vert_extends: yext,
+                col_range: (0f32, 0f32),
+                col_extends: Vec::new(),
+                outline: Outline::default(),
+            }.with_outline()
         } else {
-            PixelHex {
-                props: Props::flat(0f32, 0f32),
+            PixelHex::empty(Props::flat(0f32, 0f32))
+        }
+    }
+
+    /// Creates a pointy top `PixelHex` from columns given as `(x, (y1, y2))`.
+    pub fn pointy(vert_extends: &[(f32, (f32, f32))]) -> Self {
+
+        let xext = line_range(vert_extends);
+
+        let miny = vert_extends.iter()
+            .map(|(_x, (y1, _y2))| *y1)
+            .min_by(|&v1, &v2| v1.total_cmp(&v2));
+
+        if let Some(miny) = miny {
+            let vs = vert_extends.last().map(|(_x, (_y1, y2))| *y2).unwrap() - miny + 1.0f32;
+            let hs = xext.1 - xext.0 + 1.0f32;
+            PixelHex{
+                props: Props::pointy(hs, vs),
                 horiz_extends: Vec::new(),
                 vert_extends: (0f32, 0f32),
+                col_range: xext,
+                col_extends: vert_extends.iter().map(|(_x, y_ext)| *y_ext).collect(),
+                outline: Outline::default(),
+            }.with_outline()
+        } else {
+            PixelHex::empty(Props::pointy(0f32, 0f32))
+        }
+    }
+
+    /// Creates a `PixelHex` from a row-major alpha mask of `width` x `height` bytes.
+    /// Every byte which is at least `threshold` belongs to the hexagon and `anchor` is
+    /// the pixel which becomes the reference of the hexagon.
+    /// The mask has to tile the plane without gaps or overlaps.
+    pub fn from_mask(top: HexTop, width: usize, height: usize, data: &[u8], threshold: u8, anchor: (usize, usize)) -> Result<Self, Error> {
+        let size = width.checked_mul(height);
+        if size != Some(data.len()) {
+            return Err(Error::MaskSize { expected: size.unwrap_or(usize::MAX), found: data.len() });
+        }
+        if anchor.0 >= width || anchor.1 >= height {
+            return Err(Error::Anchor);
+        }
+        let opaque = |x: usize, y: usize| data[y * width + x] >= threshold;
+        let (runs, anchor_line, anchor_pos) = match top {
+            HexTop::FLAT => (mask_runs(height, width, |y, x| opaque(x, y))?, anchor.1, anchor.0),
+            HexTop::POINTY => (mask_runs(width, height, opaque)?, anchor.0, anchor.1),
+        };
+        if runs.is_empty() {
+            return Err(Error::Empty);
+        }
+        if !runs.iter().any(|(l, _)| *l == anchor_line) {
+            return Err(Error::Anchor);
+        }
+        let extends: Vec<(f32, (f32, f32))> = runs.iter()
+            .map(|(l, (first, last))| (
+                *l as f32 - anchor_line as f32,
+                (*first as f32 - anchor_pos as f32, *last as f32 - anchor_pos as f32)
+            ))
+            .collect();
+        let h = match top {
+            HexTop::FLAT => PixelHex::try_flat(&extends)?,
+            HexTop::POINTY => PixelHex::try_pointy(&extends)?,
+        };
+        h.check_tiling()?;
+        Ok(h)
+    }
+
+    /// Checks if the pixel `(x, y)` relative to the reference belongs to the hexagon.
+    fn contains_relative(&self, (x, y): (f32, f32)) -> bool {
+        let (line, pos, range, extends) = match self.props.top() {
+            HexTop::FLAT => (y, x, self.vert_extends, &self.horiz_extends),
+            HexTop::POINTY => (x, y, self.col_range, &self.col_extends),
+        };
+        if line.fract() != 0f32 || line < range.0 || line > range.1 {
+            return false;
+        }
+        extends.get((line - range.0) as usize)
+            .is_some_and(|ext| pos >= ext.0 && pos <= ext.1)
+    }
+
+    /// Checks that every pixel of the plane belongs to exactly one hexagon.
+    /// It is enough to check a fundamental domain of the hexagon lattice.
+    fn check_tiling(&self) -> Result<(), Error> {
+        let hs = self.horizontal_spacing() as i32;
+        let vs = self.vertical_spacing() as i32;
+        let (x0, y0, w, h) = match self.props.top() {
+            HexTop::FLAT => (self.horiz_extends.iter().map(|e| e.0 as i32).min().unwrap_or(0), self.vert_extends.0 as i32, 2 * hs, vs),
+            HexTop::POINTY => (self.col_range.0 as i32, self.col_extends.iter().map(|e| e.0 as i32).min().unwrap_or(0), hs, 2 * vs),
+        };
+        for y in y0..y0 + h {
+            for x in x0..x0 + w {
+                let (xf, yf) = (x as f32, y as f32);
+                let center = self.props.axial((xf, yf));
+                let count = (0..=3)
+                    .flat_map(|radius| center.circle(radius))
+                    .filter(|qr| self.contains(qr, (xf, yf)))
+                    .count();
+                match count {
+                    0 => return Err(Error::Gap((x, y))),
+                    1 => {},
+                    _ => return Err(Error::Overlap((x, y))),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Creates a flat top `PixelHex` like `flat`, but rejects empty, unsorted,
+    /// non-contiguous or inverted rows.
+    pub fn try_flat(horiz_extends: &[(f32, (f32, f32))]) -> Result<Self, Error> {
+        check_lines(horiz_extends)?;
+        let h = PixelHex::flat(horiz_extends);
+        h.check()?;
+        Ok(h)
+    }
+
+    /// Creates a pointy top `PixelHex` like `pointy`, but rejects empty, unsorted,
+    /// non-contiguous or inverted columns.
+    pub fn try_pointy(vert_extends: &[(f32, (f32, f32))]) -> Result<Self, Error> {
+        check_lines(vert_extends)?;
+        let h = PixelHex::pointy(vert_extends);
+        h.check()?;
+        Ok(h)
+    }
+
+    /// Checks that the spacings and the line extends fit together.
+    fn check(&self) -> Result<(), Error> {
+        let (hs, vs) = (self.horizontal_spacing(), self.vertical_spacing());
+        if !hs.is_finite() || !vs.is_finite() {
+            return Err(Error::NotFinite);
+        }
+        if hs <= 0f32 || vs <= 0f32 {
+            return Err(Error::Spacing);
+        }
+        let (range, extends) = match self.props.top() {
+            HexTop::FLAT => (self.vert_extends, &self.horiz_extends),
+            HexTop::POINTY => (self.col_range, &self.col_extends),
+        };
+        if !range.0.is_finite() || !range.1.is_finite() {
+            return Err(Error::NotFinite);
+        }
+        if extends.is_empty() {
+            return Err(Error::Empty);
+        }
+        let expected = (range.1 - range.0 + 1f32).max(0f32) as usize;
+        if extends.len() != expected {
+            return Err(Error::ExtendsLength { expected, found: extends.len() });
+        }
+        for (i, (a1, a2)) in extends.iter().enumerate() {
+            if !a1.is_finite() || !a2.is_finite() {
+                return Err(Error::NotFinite);
+            }
+            if a1 > a2 {
+                return Err(Error::Inverted(i));
             }
         }
+        Ok(())
     }
 
+    /// Pixel outline of the hexagon `(0, 0)`, clockwise with y pointing down, one pixel per step.
+    fn unit_outline(&self) -> Vec<(f32, f32)> {
+        match self.props.top() {
+            HexTop::FLAT => line_outline(&self.horiz_extends, self.vert_extends.0),
+            HexTop::POINTY => {
+                // transposing mirrors, so the direction has to be reversed
+                let mut points: Vec<(f32, f32)> = line_outline(&self.col_extends, self.col_range.0)
+                    .into_iter()
+                    .map(|(y, x)| (x, y))
+                    .collect();
+                points.reverse();
+                points
+            },
+        }
+    }
+
+    /// Index into `outline` of the corner in every diagonal of the hexagon `(0, 0)`,
+    /// the first point where it meets both neighbours of the diagonal.
+    fn corner_indices(&self, outline: &[(f32, f32)]) -> [Option<usize>; 6] {
+        let owners: Vec<[Axial; 4]> = outline.iter()
+            .map(|(x, y)| [(-0.5f32, -0.5f32), (0.5f32, -0.5f32), (0.5f32, 0.5f32), (-0.5f32, 0.5f32)]
+                .map(|(dx, dy)| self.axial((x + dx, y + dy))))
+            .collect();
+        HexDiagonal::all().map(|diag| {
+            let (a, b) = diag.directions();
+            let hexes = [Axial::default(), a.offset(), b.offset()];
+            owners.iter().position(|o| hexes.iter().all(|qr| o.contains(qr)))
+        })
+    }
+
+    /// Stores the outline of a valid hexagon, the outline of an invalid one stays empty.
+    fn with_outline(mut self) -> Self {
+        if self.check().is_ok() {
+            let points = self.unit_outline();
+            let corners = self.corner_indices(&points);
+            let vertices = (0..points.len()).filter(|&i| PixelHex::is_vertex(&points, i, &corners)).collect();
+            self.outline = Outline { points, corners, vertices };
+        }
+        self
+    }
+
+    /// Returns true if point `i` of `outline` is a corner in `keep` or not in the middle of a straight line.
+    fn is_vertex(outline: &[(f32, f32)], i: usize, keep: &[Option<usize>]) -> bool {
+        let n = outline.len();
+        keep.contains(&Some(i)) || !collinear(outline[(i + n - 1) % n], outline[i], outline[(i + 1) % n])
+    }
+
+    /// The vertices of `outline` from index `from` to index `to`, both included.
+    fn simplified(outline: &[(f32, f32)], from: usize, to: usize, keep: &[Option<usize>]) -> Vec<(f32, f32)> {
+        let n = outline.len();
+        let len = (to + n - from) % n;
+        (0..=len).map(|k| (from + k) % n)
+            .filter(|&i| i == from || i == to || PixelHex::is_vertex(outline, i, keep))
+            .map(|i| outline[i])
+            .collect()
+    }
+
+    /// Position of `corner` relative to its hexagon, if the neighbours meet there.
+    fn relative_corner(&self, corner: CornerId) -> Option<(f32, f32)> {
+        let i = self.outline.corners[corner.diagonal().index()]?;
+        Some(self.outline.points[i])
+    }
+
+    fn empty(props: Props) -> Self {
+        PixelHex {
+            props,
+            horiz_extends: Vec::new(),
+            vert_extends: (0f32, 0f32),
+            col_range: (0f32, 0f32),
+            col_extends: Vec::new(),
+            outline: Outline::default(),
+        }
+    }
 
+    /// Compute the axial index of the hexagon containing `xy`.
+    /// Returns `None` only if `xy` is NaN or infinite.
+    pub fn checked_axial(&self, xy: (f32, f32)) -> Option<Axial> {
+        if !xy.0.is_finite() || !xy.1.is_finite() {
+            return None;
+        }
+        Some(match self.props.top() {
+            HexTop::FLAT => self.flat_axial(xy),
+            HexTop::POINTY => self.pointy_axial(xy),
+        })
+    }
+
+    fn flat_axial(&self, (x, y): (f32, f32)) -> Axial {
+        let qr = self.props.axial((x, y));
+        let (_xr ,yr) = self.xy_ref(&qr);
+        let dr = step(y - yr, self.vert_extends);
+
+        let qr2 = shift(qr, 0, dr);
+
+        let (xr2 ,yr2) = self.xy_ref(&qr2);
+        let dx2 = x - xr2;
+        let dy2 = y - yr2;
+        let dq = line(&self.horiz_extends, self.vert_extends.0, dy2).map_or(0, |ext| step(dx2, ext));
+
+        let qr3 = shift(qr2, dq, 0);
+
+        let (_xr3 ,yr3) = self.xy_ref(&qr3);
+        let dr3 = step(y - yr3, self.vert_extends);
+
+        shift(qr3, 0, dr3)
+    }
+
+    fn pointy_axial(&self, (x, y): (f32, f32)) -> Axial {
+        let qr = self.props.axial((x, y));
+        let (xr, _yr) = self.xy_ref(&qr);
+        let dq = step(x - xr, self.col_range);
+
+        let qr2 = shift(qr, dq, 0);
+
+        let (xr2, yr2) = self.xy_ref(&qr2);
+        let dx2 = x - xr2;
+        let dy2 = y - yr2;
+        let dr = line(&self.col_extends, self.col_range.0, dx2).map_or(0, |ext| step(dy2, ext));
+
+        let qr3 = shift(qr2, 0, dr);
+
+        let (xr3, _yr3) = self.xy_ref(&qr3);
+        let dq3 = step(x - xr3, self.col_range);
+
+        shift(qr3, dq3, 0)
+    }
 }
 
 impl Hexagons for PixelHex {
+    fn top(&self) -> HexTop {
+        self.props.top()
+    }
+
     fn horizontal_spacing(&self) -> f32 {
         self.props.horizontal_spacing()
     }
@@ -72,27 +512,56 @@ impl Hexagons for PixelHex {
         (x - x_ref, y - y_ref)
     }
 
-    fn axial(&self, (x, y): (f32, f32)) -> Axial {
-        let qr = self.props.axial((x, y));
-        let (_xr ,yr) = self.xy_ref(&qr);
-        let dy =  y - yr;
-        let dr = -1 * (dy < self.vert_extends.0) as i32 + 1 * (dy > self.vert_extends.1) as i32;
-
-        let qr2 = qr + Axial::new(0, dr);
+    /// NaN or infinite coordinates give the hexagon `(0, 0)`.
+    fn axial(&self, xy: (f32, f32)) -> Axial {
+        self.checked_axial(xy).unwrap_or_default()
+    }
 
-        let (xr2 ,yr2) = self.xy_ref(&qr2);
-        let dx2 = x - xr2;
-        let dy2 = y - yr2;
-        let ext = self.horiz_extends[(dy2-self.vert_extends.0) as usize];
-        let dq = -1 * (dx2 < ext.0) as i32 + 1 * (dx2 > ext.1) as i32;
+    fn contains(&self, qr: &Axial, (x, y): (f32, f32)) -> bool {
+        let (xr, yr) = self.xy_ref(qr);
+        self.contains_relative((x - xr, y - yr))
+    }
 
-        let qr3 = qr2 + Axial::new(dq, 0);
+    /// The point of the pixel outline where the three hexagons meet.
+    fn corner(&self, qr: &Axial, diagonal: HexDiagonal) -> (f32, f32) {
+        let id = CornerId::new(*qr, diagonal);
+        match self.relative_corner(id) {
+            Some((x, y)) => {
+                let (xr, yr) = self.xy_ref(&id.hex());
+                (xr + x, yr + y)
+            },
+            None => lattice_corner(self, id),
+        }
+    }
 
-        let (_xr3 ,yr3) = self.xy_ref(&qr3);
-        let dy3 =  y - yr3;
-        let dr3 = -1 * (dy3 < self.vert_extends.0) as i32 + 1 * (dy3 > self.vert_extends.1) as i32;
+    /// The pixel outline, every point where it changes its direction or meets other hexagons.
+    fn corners(&self, qr: &Axial) -> Vec<(f32, f32)> {
+        let points = &self.outline.points;
+        let (xr, yr) = self.xy_ref(qr);
+        self.outline.vertices.iter()
+            .map(|&i| (xr + points[i].0, yr + points[i].1))
+            .collect()
+    }
 
-        qr3 + Axial::new(0, dr3)
+    /// The part of the pixel outline between the corners of the edge.
+    fn edge(&self, qr: &Axial, direction: HexDirection) -> Vec<(f32, f32)> {
+        let id = EdgeId::new(*qr, direction);
+        let Outline { points: outline, corners: keep, .. } = &self.outline;
+        let (a, b) = id.direction().corners();
+        let mut points = match (keep[a.index()], keep[b.index()]) {
+            (Some(from), Some(to)) => {
+                let (xr, yr) = self.xy_ref(&id.hex());
+                PixelHex::simplified(outline, from, to, keep)
+                    .into_iter()
+                    .map(|(x, y)| (xr + x, yr + y))
+                    .collect()
+            },
+            _ => vec![self.corner(&id.hex(), a), self.corner(&id.hex(), b)],
+        };
+        if id.hex() != *qr {
+            points.reverse();
+        }
+        points
     }
 }
 
@@ -230,4 +699,291 @@ mod test {
         }
     }
 
-}
+    #[test]
+    fn test_pointy() {
+        let v = vec![
+            (-1.0f32, ( 0f32, 1f32)),
+            ( 0.0f32, (-1f32, 2f32)),
+            ( 1.0f32, (-1f32, 2f32)),
+            ( 2.0f32, ( 0f32, 1f32))
+        ];
+        let h = PixelHex::pointy(&v);
+        assert_eq!(h.horizontal_spacing(), 4f32);
+        assert_eq!(h.vertical_spacing(), 3f32);
+
+        assert_eq!(h.xy_ref(&Axial::new( 0,  0)), ( 0f32,  0f32));
+        assert_eq!(h.xy_ref(&Axial::new( 1,  0)), ( 4f32,  0f32));
+        assert_eq!(h.xy_ref(&Axial::new( 0,  1)), ( 2f32,  3f32));
+        assert_eq!(h.xy_ref(&Axial::new(-1,  1)), (-2f32,  3f32));
+
+        // the pointy mask is the transposed flat mask
+        let f = PixelHex::flat(&v);
+        for x in -12..=12 {
+            for y in -12..=12 {
+                let (q, r) = f.axial((x as f32, y as f32)).to_tuple();
+                assert_eq!(h.axial((y as f32, x as f32)), Axial::new(r, q), "h.axial(({},{}))", y, x);
+            }
+        }
+    }
+
+    static POINTYPIXELHEXRON: &str = "(
+        props: (
+            top: POINTY,
+            vert_spacing: 29.0,
+            horz_spacing: 32.0,
+        ),
+        col_range: (-16.0, 15.0),
+        col_extends: [
+            (-11.0, 10.0),
+            (-11.0, 10.0),
+            (-12.0, 11.0),
+            (-12.0, 11.0),
+            (-13.0, 12.0),
+            (-13.0, 12.0),
+            (-14.0, 13.0),
+            (-14.0, 13.0),
+            (-15.0, 14.0),
+            (-15.0, 14.0),
+            (-16.0, 15.0),
+            (-16.0, 15.0),
+            (-17.0, 16.0),
+            (-17.0, 16.0),
+            (-18.0, 17.0),
+            (-18.0, 17.0),
+            (-18.0, 17.0),
+            (-18.0, 17.0),
+            (-17.0, 16.0),
+            (-17.0, 16.0),
+            (-16.0, 15.0),
+            (-16.0, 15.0),
+            (-15.0, 14.0),
+            (-15.0, 14.0),
+            (-14.0, 13.0),
+            (-14.0, 13.0),
+            (-13.0, 12.0),
+            (-13.0, 12.0),
+            (-12.0, 11.0),
+            (-12.0, 11.0),
+            (-11.0, 10.0),
+            (-11.0, 10.0),
+        ],
+    )";
+
+    #[test]
+    fn test_pointy_bigger() {
+        let h: PixelHex = ron::from_str(POINTYPIXELHEXRON).unwrap();
+        assert_eq!(h.horizontal_spacing(), 32f32);
+        assert_eq!(h.vertical_spacing(), 29f32);
+        let xmin = h.col_range.0;
+        for y in -20..=20 {
+            assert_ne!(h.axial((xmin - 1f32, y as f32)), Axial::new(0, 0), "h.axial(({},{}))", xmin - 1.0f32, y as f32);
+        }
+        for (i, (y1, y2)) in h.col_extends.iter().enumerate() {
+            for y in (*y1 as i32-3)..=(*y1 as i32 - 1) {
+                assert_ne!(h.axial((xmin + i as f32, y as f32)), Axial::new(0,0 ), "h.axial(({},{}))", xmin, y as f32);
+            }
+            for y in (*y1 as i32)..=(*y2 as i32) {
+                assert_eq!(h.axial((xmin + i as f32, y as f32)), Axial::new(0,0 ), "h.axial(({},{}))", xmin, y as f32);
+            }
+            for y in (*y2 as i32+1)..=(*y2 as i32 +5) {
+                assert_ne!(h.axial((xmin + i as f32, y as f32)), Axial::new(0,0 ), "h.axial(({},{}))", xmin, y as f32);
+            }
+        }
+        for y in -20..=20 {
+            assert_ne!(h.axial((h.col_range.1 + 1f32, y as f32)), Axial::new(0, 0), "h.axial(({},{}))", xmin, y as f32);
+        }
+    }
+
+    fn mask(rows: &[&str]) -> (usize, usize, Vec<u8>) {
+        let data: Vec<u8> = rows.iter()
+            .flat_map(|row| row.bytes().map(|b| if b == b'X' { 255u8 } else { 0u8 }))
+            .collect();
+        (rows[0].len(), rows.len(), data)
+    }
+
+    #[test]
+    fn test_from_mask() {
+        let (w, h, data) = mask(&[
+            ".XX.",
+            "XXXX",
+            "XXXX",
+            ".XX.",
+        ]);
+        let v = vec![
+            (-1.0f32, ( 0f32, 1f32)),
+            ( 0.0f32, (-1f32, 2f32)),
+            ( 1.0f32, (-1f32, 2f32)),
+            ( 2.0f32, ( 0f32, 1f32))
+        ];
+        let f = PixelHex::from_mask(HexTop::FLAT, w, h, &data, 128, (1, 1)).unwrap();
+        let f2 = PixelHex::flat(&v);
+        assert_eq!(f.horizontal_spacing(), f2.horizontal_spacing());
+        assert_eq!(f.vertical_spacing(), f2.vertical_spacing());
+        assert_eq!(f.vert_extends, f2.vert_extends);
+        assert_eq!(f.horiz_extends, f2.horiz_extends);
+
+        let p = PixelHex::from_mask(HexTop::POINTY, w, h, &data, 128, (1, 1)).unwrap();
+        let p2 = PixelHex::pointy(&v);
+        assert_eq!(p.horizontal_spacing(), p2.horizontal_spacing());
+        assert_eq!(p.vertical_spacing(), p2.vertical_spacing());
+        assert_eq!(p.col_range, p2.col_range);
+        assert_eq!(p.col_extends, p2.col_extends);
+    }
+
+    #[test]
+    fn test_from_mask_bigger() {
+        let h: PixelHex = ron::from_str(PIXELHEXRON).unwrap();
+        let (w, ht) = (36usize, h.horiz_extends.len());
+        let mut data = vec![0u8; w * ht];
+        for (y, (x1, x2)) in h.horiz_extends.iter().enumerate() {
+            for x in (*x1 as i32)..=(*x2 as i32) {
+                data[y * w + (x + 18) as usize] = 200u8;
+            }
+        }
+        let m = PixelHex::from_mask(HexTop::FLAT, w, ht, &data, 100, (18, 16)).unwrap();
+        assert_eq!(m.horizontal_spacing(), h.horizontal_spacing());
+        assert_eq!(m.vertical_spacing(), h.vertical_spacing());
+        assert_eq!(m.vert_extends, h.vert_extends);
+        assert_eq!(m.horiz_extends, h.horiz_extends);
+        assert_eq!(PixelHex::from_mask(HexTop::FLAT, w, ht, &data, 201, (18, 16)).err(), Some(Error::Empty));
+    }
+
+    #[test]
+    fn test_from_mask_errors() {
+        let (w, h, data) = mask(&[
+            "XXX",
+            "XXX",
+            "XXX",
+        ]);
+        assert_eq!(PixelHex::from_mask(HexTop::FLAT, w, h, &data[1..], 1, (1, 1)).err(), Some(Error::MaskSize { expected: 9, found: 8 }));
+        assert!(matches!(PixelHex::from_mask(HexTop::FLAT, w, h, &data, 1, (1, 1)), Err(Error::Gap(_)) | Err(Error::Overlap(_))));
+
+        let (w, h, data) = mask(&[
+            "X.X",
+            "XXX",
+        ]);
+        assert_eq!(PixelHex::from_mask(HexTop::FLAT, w, h, &data, 1, (1, 1)).err(), Some(Error::NotContiguous(0)));
+
+        let (w, h, data) = mask(&[
+            ".XX.",
+            "....",
+            ".XX.",
+        ]);
+        assert_eq!(PixelHex::from_mask(HexTop::FLAT, w, h, &data, 1, (1, 0)).err(), Some(Error::NotContiguous(2)));
+        assert_eq!(PixelHex::from_mask(HexTop::FLAT, w, h, &data, 1, (1, 1)).err(), Some(Error::NotContiguous(2)));
+
+        let (w, h, data) = mask(&[
+            ".XX.",
+            "XXXX",
+            "XXXX",
+            ".XX.",
+            "....",
+        ]);
+        assert_eq!(PixelHex::from_mask(HexTop::FLAT, w, h, &data, 1, (1, 4)).err(), Some(Error::Anchor));
+        assert_eq!(PixelHex::from_mask(HexTop::FLAT, w, h, &data, 1, (1, 5)).err(), Some(Error::Anchor));
+        assert_eq!(PixelHex::from_mask(HexTop::FLAT, w, h, &data, 1, (9, 1)).err(), Some(Error::Anchor));
+        assert_eq!(PixelHex::from_mask(HexTop::POINTY, w, h, &data, 1, (4, 1)).err(), Some(Error::Anchor));
+
+        // width times height wraps around to 0
+        let huge = usize::MAX / 2 + 1;
+        assert_eq!(PixelHex::from_mask(HexTop::FLAT, huge, 2, &[], 1, (0, 0)).err(), Some(Error::MaskSize { expected: usize::MAX, found: 0 }));
+    }
+
+
+    #[test]
+    fn test_try_flat() {
+        let v = vec![
+            (-1.0f32, ( 0f32, 1f32)),
+            ( 0.0f32, (-1f32, 2f32)),
+            ( 1.0f32, (-1f32, 2f32)),
+            ( 2.0f32, ( 0f32, 1f32))
+        ];
+        let h = PixelHex::try_flat(&v).unwrap();
+        assert_eq!(h.horizontal_spacing(), 3f32);
+        assert!(PixelHex::try_pointy(&v).is_ok());
+
+        assert_eq!(PixelHex::try_flat(&[]).err(), Some(Error::Empty));
+        let mut w = v.clone();
+        w.swap(1, 2);
+        assert_eq!(PixelHex::try_flat(&w).err(), Some(Error::NotContiguous(1)));
+        let mut w = v.clone();
+        w[3].0 = 3f32;
+        assert_eq!(PixelHex::try_flat(&w).err(), Some(Error::NotContiguous(3)));
+        let mut w = v.clone();
+        w[2].1 = (2f32, -1f32);
+        assert_eq!(PixelHex::try_pointy(&w).err(), Some(Error::Inverted(2)));
+        let mut w = v.clone();
+        w[0].1 .0 = f32::NAN;
+        assert_eq!(PixelHex::try_flat(&w).err(), Some(Error::NotFinite));
+        // rows have to include the reference
+        let w: Vec<(f32, (f32, f32))> = v.iter().map(|(y, ext)| (y + 3f32, *ext)).collect();
+        assert_eq!(PixelHex::try_flat(&w).err(), Some(Error::ExtendsLength { expected: 6, found: 4 }));
+    }
+
+    #[test]
+    fn test_bad_ron() {
+        let ron_str = PIXELHEXRON.replace("(-11.0, 10.0),\n        ],", "],");
+        let err = ron::from_str::<PixelHex>(&ron_str).unwrap_err();
+        assert!(err.to_string().contains("31 line extends given, expected 32"), "{}", err);
+
+        let ron_str = PIXELHEXRON.replace("horz_spacing: 29.0", "horz_spacing: 0.0");
+        let err = ron::from_str::<PixelHex>(&ron_str).unwrap_err();
+        assert!(err.to_string().contains("spacings must be positive"), "{}", err);
+
+        let ron_str = POINTYPIXELHEXRON.replace("(-14.0, 13.0),", "(14.0, 13.0),");
+        let err = ron::from_str::<PixelHex>(&ron_str).unwrap_err();
+        assert!(err.to_string().contains("line 6 ends before it starts"), "{}", err);
+
+        let h: PixelHex = ron::from_str(PIXELHEXRON).unwrap();
+        let h2: PixelHex = ron::from_str(&ron::to_string(&h).unwrap()).unwrap();
+        assert_eq!(h2.horiz_extends, h.horiz_extends);
+    }
+
+
+    /// Pseudo random coordinates spread over all magnitudes of f32.
+    fn sweep(n: usize) -> impl Iterator<Item = (f32, f32)> {
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        (0..n).map(move |_| {
+            let v = next();
+            let scale = 10f32.powi((v % 39) as i32);
+            let x = ((v >> 8) % 2001) as f32 / 1000f32 - 1f32;
+            let y = ((v >> 24) % 2001) as f32 / 1000f32 - 1f32;
+            (x * scale, y * scale)
+        })
+    }
+
+    #[test]
+    fn test_checked_axial() {
+        let flat: PixelHex = ron::from_str(PIXELHEXRON).unwrap();
+        let pointy: PixelHex = ron::from_str(POINTYPIXELHEXRON).unwrap();
+        for h in [&flat, &pointy] {
+            for xy in sweep(100_000) {
+                assert!(h.checked_axial(xy).is_some(), "h.checked_axial({:?})", xy);
+            }
+            for xy in [(f32::MAX, f32::MIN), (f32::MIN, f32::MIN_POSITIVE), (1e30f32, -1e30f32), (0.5f32, -15.5f32)] {
+                assert!(h.checked_axial(xy).is_some(), "h.checked_axial({:?})", xy);
+            }
+            for xy in [(f32::NAN, 0f32), (0f32, f32::INFINITY), (f32::NEG_INFINITY, f32::NAN)] {
+                assert_eq!(h.checked_axial(xy), None);
+                assert_eq!(h.axial(xy), Axial::default());
+            }
+            // pixels stay in the hexagon they are mapped to
+            for (x, y) in sweep(20_000) {
+                let (x, y) = ((x % 5000f32).round(), (y % 5000f32).round());
+                let qr = h.axial((x, y));
+                assert!(h.contains(&qr, (x, y)), "h.axial(({},{})) = {:?}", x, y, qr);
+            }
+        }
+        let empty = PixelHex::flat(&[]);
+        for xy in sweep(1000) {
+            assert!(empty.checked_axial(xy).is_some());
+        }
+    }
+
+}
\ No newline at end of file
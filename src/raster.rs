@@ -0,0 +1,302 @@
+//! Find the hexagons covered by rectangles, polygons and circles in pixel space.
+//!
+//! Candidates are found by walking from hexagon to hexagon inside the bounding box
+//! of the shape, so the work grows with the number of hexagons and not with the pixels.
+use std::collections::{HashSet, VecDeque};
+
+use super::axial::Axial;
+use super::Hexagons;
+
+/// A shape in pixel coordinates.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    /// An axis aligned rectangle, both corners included.
+    Rect { min: (f32, f32), max: (f32, f32) },
+    /// A simple polygon, possibly concave. Points on the outline are inside.
+    Polygon(Vec<(f32, f32)>),
+    Circle { center: (f32, f32), radius: f32 },
+}
+
+/// Which hexagons count as covered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RasterMode {
+    /// The reference point `xy_ref` is inside the shape.
+    Center,
+    /// The shape and the area of the hexagon intersect.
+    Overlap,
+}
+
+impl Shape {
+    fn bounding_box(&self) -> Option<((f32, f32), (f32, f32))> {
+        match self {
+            Shape::Rect { min, max } => Some((*min, *max)),
+            Shape::Polygon(points) => {
+                let first = *points.first()?;
+                Some(points.iter().fold((first, first), |((x1, y1), (x2, y2)), (x, y)| {
+                    ((x1.min(*x), y1.min(*y)), (x2.max(*x), y2.max(*y)))
+                }))
+            },
+            Shape::Circle { center: (x, y), radius } => Some(((x - radius, y - radius), (x + radius, y + radius))),
+        }
+    }
+
+    fn outline(&self) -> Vec<(f32, f32)> {
+        match self {
+            Shape::Rect { min: (x1, y1), max: (x2, y2) } => vec![(*x1, *y1), (*x2, *y1), (*x2, *y2), (*x1, *y2)],
+            Shape::Polygon(points) => points.clone(),
+            Shape::Circle { .. } => Vec::new(),
+        }
+    }
+
+    /// Returns true if the point `xy` is inside the shape or on its outline.
+    pub fn contains(&self, (x, y): (f32, f32)) -> bool {
+        match self {
+            Shape::Rect { min: (x1, y1), max: (x2, y2) } => *x1 <= x && x <= *x2 && *y1 <= y && y <= *y2,
+            Shape::Polygon(points) => in_polygon(points, (x, y)),
+            Shape::Circle { center: (cx, cy), radius } => (x - cx).hypot(y - cy) <= *radius,
+        }
+    }
+
+    /// Returns true if the shape intersects the outline `hexagon`.
+    fn overlaps(&self, hexagon: &[(f32, f32)]) -> bool {
+        if let Shape::Circle { center, radius } = self {
+            return in_polygon(hexagon, *center) || distance_to_outline(hexagon, *center) <= *radius;
+        }
+        let outline = self.outline();
+        hexagon.iter().any(|xy| self.contains(*xy))
+            || outline.iter().any(|xy| in_polygon(hexagon, *xy))
+            || edges(&outline).any(|a| edges(hexagon).any(|b| intersect(a, b)))
+    }
+}
+
+fn edges(points: &[(f32, f32)]) -> impl Iterator<Item = ((f32, f32), (f32, f32))> + '_ {
+    points.iter().zip(points.iter().cycle().skip(1)).map(|(a, b)| (*a, *b))
+}
+
+fn cross((ox, oy): (f32, f32), (ax, ay): (f32, f32), (bx, by): (f32, f32)) -> f32 {
+    (ax - ox) * (by - oy) - (ay - oy) * (bx - ox)
+}
+
+/// Distance of `p` to the segment from `a` to `b`.
+fn distance_to_segment(p: (f32, f32), (a, b): ((f32, f32), (f32, f32))) -> f32 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let len2 = dx * dx + dy * dy;
+    let t = if len2 > 0f32 {
+        (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / len2).clamp(0f32, 1f32)
+    } else {
+        0f32
+    };
+    (p.0 - a.0 - t * dx).hypot(p.1 - a.1 - t * dy)
+}
+
+fn distance_to_outline(points: &[(f32, f32)], p: (f32, f32)) -> f32 {
+    edges(points).map(|e| distance_to_segment(p, e)).fold(f32::INFINITY, f32::min)
+}
+
+/// Even-odd rule, points on the outline are inside.
+fn in_polygon(points: &[(f32, f32)], (x, y): (f32, f32)) -> bool {
+    if edges(points).any(|e| distance_to_segment((x, y), e) == 0f32) {
+        return true;
+    }
+    edges(points).filter(|((x1, y1), (x2, y2))| {
+        (*y1 > y) != (*y2 > y) && x < x1 + (y - y1) * (x2 - x1) / (y2 - y1)
+    }).count() % 2 == 1
+}
+
+/// Returns true if the two segments touch or cross.
+fn intersect((a, b): ((f32, f32), (f32, f32)), (c, d): ((f32, f32), (f32, f32))) -> bool {
+    let d1 = cross(c, d, a);
+    let d2 = cross(c, d, b);
+    let d3 = cross(a, b, c);
+    let d4 = cross(a, b, d);
+    if ((d1 > 0f32 && d2 < 0f32) || (d1 < 0f32 && d2 > 0f32)) && ((d3 > 0f32 && d4 < 0f32) || (d3 < 0f32 && d4 > 0f32)) {
+        return true;
+    }
+    distance_to_segment(a, (c, d)) == 0f32
+        || distance_to_segment(b, (c, d)) == 0f32
+        || distance_to_segment(c, (a, b)) == 0f32
+        || distance_to_segment(d, (a, b)) == 0f32
+}
+
+/// All hexagons covered by `shape`, sorted by rows `r` and then by `q`.
+pub fn rasterize<H: Hexagons + ?Sized>(hexagons: &H, shape: &Shape, mode: RasterMode) -> Vec<Axial> {
+    let Some(((x1, y1), (x2, y2))) = shape.bounding_box() else {
+        return Vec::new();
+    };
+    if !(x1 <= x2 && y1 <= y2) {
+        return Vec::new();
+    }
+    // a hexagon can only reach the box if its reference point is close enough
+    let margin = hexagons.horizontal_spacing().abs() + hexagons.vertical_spacing().abs();
+    let near = |qr: &Axial| {
+        let (x, y) = hexagons.xy_ref(qr);
+        x1 - margin <= x && x <= x2 + margin && y1 - margin <= y && y <= y2 + margin
+    };
+    let start = hexagons.axial(((x1 + x2) / 2f32, (y1 + y2) / 2f32));
+    let mut seen = HashSet::from([start]);
+    let mut queue = VecDeque::from([start]);
+    let mut covered = Vec::new();
+    while let Some(qr) = queue.pop_front() {
+        let hit = match mode {
+            RasterMode::Center => shape.contains(hexagons.xy_ref(&qr)),
+            RasterMode::Overlap => shape.overlaps(&hexagons.corners(&qr)),
+        };
+        if hit {
+            covered.push(qr);
+        }
+        for n in qr.neighbours() {
+            if near(&n) && seen.insert(n) {
+                queue.push_back(n);
+            }
+        }
+    }
+    covered.sort_by_key(|qr| {
+        let (q, r) = qr.to_tuple();
+        (r, q)
+    });
+    covered
+}
+
+/// All hexagons which could touch the rectangle from `min_xy` to `max_xy`,
+/// sorted by the y and then the x coordinate of their reference points.
+pub fn viewport<H: Hexagons + ?Sized>(hexagons: &H, min_xy: (f32, f32), max_xy: (f32, f32)) -> Vec<Axial> {
+    let mut hexes = rasterize(hexagons, &Shape::Rect { min: min_xy, max: max_xy }, RasterMode::Overlap);
+    hexes.sort_by(|a, b| {
+        let (xa, ya) = hexagons.xy_ref(a);
+        let (xb, yb) = hexagons.xy_ref(b);
+        ya.total_cmp(&yb).then(xa.total_cmp(&xb))
+    });
+    hexes
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex::Props;
+    use crate::pixelhex::PixelHex;
+
+    /// All hexagons containing a pixel of the shape, by brute force.
+    fn probe<H: Hexagons>(h: &H, shape: &Shape, step: f32) -> HashSet<Axial> {
+        let ((x1, y1), (x2, y2)) = shape.bounding_box().unwrap();
+        let mut found = HashSet::new();
+        let mut y = y1;
+        while y <= y2 {
+            let mut x = x1;
+            while x <= x2 {
+                if shape.contains((x, y)) {
+                    found.insert(h.axial((x, y)));
+                }
+                x += step;
+            }
+            y += step;
+        }
+        found
+    }
+
+    fn shapes() -> Vec<Shape> {
+        vec![
+            Shape::Rect { min: (-13f32, -7f32), max: (31f32, 22f32) },
+            Shape::Polygon(vec![(-20.5f32, -19.25f32), (40f32, -5f32), (0f32, 0f32), (10f32, 35f32)]),
+            Shape::Circle { center: (3f32, -4f32), radius: 17f32 },
+        ]
+    }
+
+    #[test]
+    fn test_center() {
+        let h = Props::flat(7f32, 10f32);
+        for shape in shapes() {
+            let hexes = rasterize(&h, &shape, RasterMode::Center);
+            let expected: HashSet<Axial> = Axial::default().range(20)
+                .filter(|qr| shape.contains(h.xy_ref(qr)))
+                .collect();
+            assert_eq!(hexes.len(), expected.len());
+            assert_eq!(hexes.iter().copied().collect::<HashSet<_>>(), expected);
+        }
+        let one = Shape::Rect { min: (0f32, 0f32), max: (0f32, 0f32) };
+        assert_eq!(rasterize(&h, &one, RasterMode::Center), vec![Axial::default()]);
+        let empty = Shape::Rect { min: (1f32, 0f32), max: (0f32, 0f32) };
+        assert_eq!(rasterize(&h, &empty, RasterMode::Center), vec![]);
+        assert_eq!(rasterize(&h, &Shape::Polygon(vec![]), RasterMode::Overlap), vec![]);
+    }
+
+    #[test]
+    fn test_overlap() {
+        for h in [Props::flat(7f32, 10f32), Props::pointy(10f32, 7f32)] {
+            for shape in shapes() {
+                let hexes: HashSet<Axial> = rasterize(&h, &shape, RasterMode::Overlap).into_iter().collect();
+                let centers: HashSet<Axial> = rasterize(&h, &shape, RasterMode::Center).into_iter().collect();
+                assert!(centers.is_subset(&hexes));
+                let probed = probe(&h, &shape, 0.25f32);
+                assert!(probed.is_subset(&hexes), "{:?}", probed.difference(&hexes));
+                // only hexagons touched at the border are missed by probing
+                for qr in hexes.difference(&probed) {
+                    assert!(qr.neighbours().any(|n| probed.contains(&n)));
+                }
+            }
+        }
+        let h = Props::flat(7f32, 10f32);
+        let dot = Shape::Circle { center: (0f32, 0f32), radius: 1f32 };
+        assert_eq!(rasterize(&h, &dot, RasterMode::Overlap), vec![Axial::default()]);
+        let sorted = rasterize(&h, &shapes()[0], RasterMode::Overlap);
+        assert!(sorted.windows(2).all(|w| (w[0].to_tuple().1, w[0].to_tuple().0) < (w[1].to_tuple().1, w[1].to_tuple().0)));
+    }
+
+    #[test]
+    fn test_pixelhex() {
+        let h = PixelHex::flat(&[
+            (-1.0f32, ( 0f32, 1f32)),
+            ( 0.0f32, (-1f32, 2f32)),
+            ( 1.0f32, (-1f32, 2f32)),
+            ( 2.0f32, ( 0f32, 1f32))
+        ]);
+        let shape = Shape::Rect { min: (-4f32, -5f32), max: (7f32, 6f32) };
+        let centers = rasterize(&h, &shape, RasterMode::Center);
+        assert_eq!(centers, vec![
+            Axial::new(2, -2),
+            Axial::new(0, -1), Axial::new(1, -1), Axial::new(2, -1),
+            Axial::new(-1, 0), Axial::new(0, 0), Axial::new(1, 0), Axial::new(2, 0),
+            Axial::new(-1, 1), Axial::new(0, 1), Axial::new(1, 1),
+            Axial::new(-1, 2),
+        ]);
+        let hexes: HashSet<Axial> = rasterize(&h, &shape, RasterMode::Overlap).into_iter().collect();
+        assert!(centers.iter().all(|qr| hexes.contains(qr)));
+        assert!(hexes.contains(&Axial::new(-2, 0)));
+    }
+
+    #[test]
+    fn test_viewport() {
+        let v = [
+            (-1.0f32, ( 0f32, 1f32)),
+            ( 0.0f32, (-1f32, 2f32)),
+            ( 1.0f32, (-1f32, 2f32)),
+            ( 2.0f32, ( 0f32, 1f32))
+        ];
+        let hexagons: Vec<Box<dyn Hexagons>> = vec![
+            Box::new(Props::flat(7f32, 10f32)),
+            Box::new(Props::pointy(10f32, 7f32)),
+            Box::new(PixelHex::flat(&v)),
+            Box::new(PixelHex::pointy(&v)),
+        ];
+        let (min, max) = ((-16f32, -9f32), (23f32, 12f32));
+        for h in hexagons {
+            let hexes: Vec<Axial> = h.hexes_in_viewport(min, max).collect();
+            let unique: HashSet<Axial> = hexes.iter().copied().collect();
+            assert_eq!(unique.len(), hexes.len());
+            let mut y = min.1;
+            while y <= max.1 {
+                let mut x = min.0;
+                while x <= max.0 {
+                    assert!(unique.contains(&h.axial((x, y))), "{:?}", (x, y));
+                    x += 0.5f32;
+                }
+                y += 0.5f32;
+            }
+            for w in hexes.windows(2) {
+                let (xa, ya) = h.xy_ref(&w[0]);
+                let (xb, yb) = h.xy_ref(&w[1]);
+                assert!(ya < yb || (ya == yb && xa < xb));
+            }
+        }
+    }
+}
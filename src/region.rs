@@ -0,0 +1,138 @@
+//! Serializable descriptions of sets of hexagons.
+//!
+//! The iterators of `Axial` cannot be stored, a `Region` keeps what they were created from.
+use serde::{Deserialize, Serialize};
+
+use super::axial::Axial;
+use super::map::MapShape;
+
+/// A set of hexagons given by its parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Region {
+    /// The hexagons at exactly `radius` from `center`, like `Axial::circle`.
+    Ring { center: Axial, radius: u32 },
+    /// All hexagons within `radius` of `center`, like `Axial::range`.
+    Range { center: Axial, radius: u32 },
+    /// A `MapShape` moved from the origin to `origin`.
+    Shape { origin: Axial, shape: MapShape },
+}
+
+impl Region {
+    /// Checks if `qr` is part of the region.
+    pub fn contains(&self, qr: Axial) -> bool {
+        match *self {
+            Region::Ring { center, radius } => qr.distance_to(center) == radius,
+            Region::Range { center, radius } => qr.distance_to(center) <= radius,
+            Region::Shape { origin, shape } => shape.contains(qr.wrapping_sub(origin)),
+        }
+    }
+
+    /// All hexagons of the region, in the order of the iterator or shape it describes.
+    /// Shapes wrap around at the limits of `i32` like `contains` does.
+    ///
+    /// A `Range` has about `3 * radius²` hexagons, only collect the ones of trusted regions.
+    pub fn hexes(&self) -> Box<dyn Iterator<Item = Axial>> {
+        match *self {
+            Region::Ring { center, radius } => Box::new(center.circle(radius)),
+            Region::Range { center, radius } => Box::new(center.range(radius)),
+            Region::Shape { origin, shape } => Box::new(shape.hexes().map(move |qr| qr.wrapping_add(origin))),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HexTop;
+
+    fn regions() -> Vec<Region> {
+        vec![
+            Region::Ring { center: Axial::new(2, -1), radius: 0 },
+            Region::Ring { center: Axial::new(2, -1), radius: 3 },
+            Region::Range { center: Axial::new(-4, 7), radius: 2 },
+            Region::Shape { origin: Axial::new(1, 1), shape: MapShape::Triangle { size: 3 } },
+            Region::Shape { origin: Axial::default(), shape: MapShape::Parallelogram { q: (-2, 3), r: (1, 4) } },
+            Region::Shape { origin: Axial::new(-3, 0), shape: MapShape::Rectangle { top: HexTop::FLAT, width: 4, height: 3 } },
+        ]
+    }
+
+    #[test]
+    fn test_hexes() {
+        for region in regions() {
+            let hexes: Vec<Axial> = region.hexes().collect();
+            assert!(!hexes.is_empty());
+            for qr in &hexes {
+                assert!(region.contains(*qr), "{:?} {:?}", region, qr);
+            }
+            let around = match region {
+                Region::Ring { center, radius } | Region::Range { center, radius } => center.range(radius + 1),
+                Region::Shape { origin, .. } => origin.range(8),
+            };
+            assert_eq!(around.filter(|qr| region.contains(*qr)).count(), hexes.len());
+        }
+        assert_eq!(Region::Ring { center: Axial::default(), radius: 2 }.hexes().count(), 12);
+    }
+
+    #[test]
+    fn test_limits() {
+        let edge = Region::Shape { origin: Axial::new(i32::MAX, 0), shape: MapShape::Triangle { size: 3 } };
+        for qr in edge.hexes() {
+            assert!(edge.contains(qr), "{:?}", qr);
+        }
+        assert!(edge.hexes().any(|qr| qr.to_tuple().0 == i32::MIN));
+
+        let huge = Region::Range { center: Axial::default(), radius: 1_000_000_000 };
+        assert_eq!(huge.hexes().take(3).count(), 3);
+    }
+
+    #[test]
+    fn test_axial_serde() {
+        let qr = Axial::new(3, -7);
+        assert_eq!(ron::to_string(&qr).unwrap(), "(3,-7)");
+        assert_eq!(serde_json::to_string(&qr).unwrap(), "[3,-7]");
+        assert_eq!(ron::from_str::<Axial>("(3, -7)").unwrap(), qr);
+        assert_eq!(serde_json::from_str::<Axial>("[3,-7]").unwrap(), qr);
+        assert!(serde_json::from_str::<Axial>("[3]").is_err());
+
+        let path = vec![Axial::default(), Axial::new(1, 0), Axial::new(1, 1)];
+        let s = serde_json::to_string(&path).unwrap();
+        assert_eq!(s, "[[0,0],[1,0],[1,1]]");
+        assert_eq!(serde_json::from_str::<Vec<Axial>>(&s).unwrap(), path);
+
+        let wide = Axial::<i64>::from_qr(1 << 40, -5);
+        assert_eq!(ron::from_str::<Axial<i64>>(&ron::to_string(&wide).unwrap()).unwrap(), wide);
+        assert!(serde_json::from_str::<Axial<i8>>("[300,0]").is_err());
+    }
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct Unit {
+        #[serde(with = "crate::axial::as_struct")]
+        position: Axial,
+    }
+
+    #[test]
+    fn test_axial_struct() {
+        let unit = Unit { position: Axial::new(-2, 5) };
+        let s = serde_json::to_string(&unit).unwrap();
+        assert_eq!(s, r#"{"position":{"q":-2,"r":5}}"#);
+        assert_eq!(serde_json::from_str::<Unit>(&s).unwrap(), unit);
+        let s = ron::to_string(&unit).unwrap();
+        assert_eq!(s, "(position:(q:-2,r:5))");
+        assert_eq!(ron::from_str::<Unit>(&s).unwrap(), unit);
+    }
+
+    #[test]
+    fn test_region_serde() {
+        let regions = regions();
+        let s = ron::to_string(&regions).unwrap();
+        assert_eq!(ron::from_str::<Vec<Region>>(&s).unwrap(), regions);
+        let s = serde_json::to_string(&regions).unwrap();
+        assert_eq!(serde_json::from_str::<Vec<Region>>(&s).unwrap(), regions);
+
+        let range: Region = ron::from_str("Range(center: (1, 2), radius: 1)").unwrap();
+        assert!(range.hexes().eq(Axial::new(1, 2).range(1)));
+        let ring: Region = serde_json::from_str(r#"{"Ring":{"center":[0,0],"radius":1}}"#).unwrap();
+        assert!(ring.hexes().eq(Axial::default().neighbours()));
+    }
+}
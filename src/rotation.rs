@@ -0,0 +1,150 @@
+//! The symmetries of the hexagonal grid: six rotations and six reflections.
+use super::axial::Axial;
+
+/// An element of the dihedral group of the hexagon.
+/// Applying it first mirrors at the q axis if `reflect` is set and then
+/// rotates `steps` times by 60° clockwise.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HexRotation {
+    steps: u8,
+    reflect: bool,
+}
+
+impl HexRotation {
+    pub fn new(steps: i32, reflect: bool) -> Self {
+        HexRotation {
+            steps: steps.rem_euclid(6) as u8,
+            reflect,
+        }
+    }
+
+    pub fn identity() -> Self {
+        HexRotation::default()
+    }
+
+    /// Rotation by `steps` times 60°, clockwise for positive `steps`.
+    pub fn rotation(steps: i32) -> Self {
+        HexRotation::new(steps, false)
+    }
+
+    /// Mirror at the q axis.
+    pub fn reflection_q() -> Self {
+        HexRotation::new(0, true)
+    }
+
+    /// Mirror at the r axis.
+    pub fn reflection_r() -> Self {
+        HexRotation::new(4, true)
+    }
+
+    /// Mirror at the s axis.
+    pub fn reflection_s() -> Self {
+        HexRotation::new(2, true)
+    }
+
+    /// All twelve elements, the rotations first.
+    pub fn all() -> [HexRotation; 12] {
+        let mut all = [HexRotation::default(); 12];
+        for (i, rot) in all.iter_mut().enumerate() {
+            *rot = HexRotation::new(i as i32 % 6, i >= 6);
+        }
+        all
+    }
+
+    pub fn steps(&self) -> i32 {
+        self.steps as i32
+    }
+
+    pub fn is_reflection(&self) -> bool {
+        self.reflect
+    }
+
+    /// The symmetry which applies `self` first and then `other`.
+    pub fn then(&self, other: Self) -> Self {
+        // mirroring turns the direction of the rotation around
+        let steps = if other.reflect {
+            other.steps() - self.steps()
+        } else {
+            other.steps() + self.steps()
+        };
+        HexRotation::new(steps, self.reflect != other.reflect)
+    }
+
+    /// The symmetry which undoes `self`.
+    pub fn inverse(&self) -> Self {
+        if self.reflect {
+            *self
+        } else {
+            HexRotation::rotation(-self.steps())
+        }
+    }
+
+    /// Apply to `qr` with the origin as center.
+    pub fn apply(&self, qr: Axial) -> Axial {
+        let qr = if self.reflect { qr.reflect_q() } else { qr };
+        qr.rotate_around(Axial::default(), self.steps())
+    }
+
+    /// Apply to `qr` with `pivot` as center.
+    pub fn apply_around(&self, pivot: Axial, qr: Axial) -> Axial {
+        self.apply(qr - pivot) + pivot
+    }
+
+    /// Apply to all hexagons of a collection with `pivot` as center.
+    pub fn apply_all<I: IntoIterator<Item = Axial>>(&self, pivot: Axial, hexes: I) -> impl Iterator<Item = Axial> {
+        let rot = *self;
+        hexes.into_iter().map(move |qr| rot.apply_around(pivot, qr))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_axial() {
+        let a = Axial::new(2, -3);
+        assert_eq!(a.rotate_cw(), Axial::new(3, -1));
+        assert_eq!(a.rotate_ccw(), Axial::new(-1, -2));
+        assert_eq!(a.rotate_cw().rotate_ccw(), a);
+        assert_eq!(a.rotate_around(Axial::default(), 6), a);
+        assert_eq!(a.rotate_around(Axial::default(), -1), a.rotate_ccw());
+        assert_eq!(a.rotate_around(Axial::new(1, -1), 3), Axial::new(0, 1));
+        assert_eq!(a.reflect_q(), Axial::new(2, 1));
+        assert_eq!(a.reflect_r(), Axial::new(1, -3));
+        assert_eq!(a.reflect_s(), Axial::new(-3, 2));
+        for qr in [a.reflect_q(), a.reflect_r(), a.reflect_s(), a.rotate_cw()] {
+            assert_eq!(qr.length(), a.length());
+        }
+    }
+
+    #[test]
+    fn test_group() {
+        let hexes: Vec<Axial> = Axial::new(1, 2).spiral(2).collect();
+        assert_eq!(HexRotation::reflection_q().apply(hexes[3]), hexes[3].reflect_q());
+        assert_eq!(HexRotation::reflection_r().apply(hexes[3]), hexes[3].reflect_r());
+        assert_eq!(HexRotation::reflection_s().apply(hexes[3]), hexes[3].reflect_s());
+        for a in HexRotation::all() {
+            assert_eq!(a.then(a.inverse()), HexRotation::identity());
+            assert_eq!(a.inverse().then(a), HexRotation::identity());
+            for b in HexRotation::all() {
+                let ab = a.then(b);
+                for qr in &hexes {
+                    assert_eq!(ab.apply(*qr), b.apply(a.apply(*qr)));
+                }
+            }
+        }
+        let images: std::collections::HashSet<Axial> = HexRotation::all().iter().map(|rot| rot.apply(Axial::new(3, -1))).collect();
+        assert_eq!(images.len(), 12);
+    }
+
+    #[test]
+    fn test_apply_all() {
+        let pivot = Axial::new(-1, 1);
+        let prefab = vec![pivot, pivot + Axial::new(1, 0), pivot + Axial::new(2, 0)];
+        let rotated: Vec<Axial> = HexRotation::rotation(2).apply_all(pivot, prefab.clone()).collect();
+        assert_eq!(rotated, vec![pivot, pivot + Axial::new(-1, 1), pivot + Axial::new(-2, 2)]);
+        let mirrored: Vec<Axial> = HexRotation::reflection_s().apply_all(pivot, prefab).collect();
+        assert_eq!(mirrored, vec![pivot, pivot + Axial::new(0, 1), pivot + Axial::new(0, 2)]);
+    }
+}
@@ -0,0 +1,211 @@
+//! Check that a `Hexagons` implementation tiles the plane.
+//!
+//! Every pixel has to be contained in exactly one hexagon, which is the one `axial` returns.
+//! The pixels which `axial` maps to the hexagon `(0, 0)` define the reference shape and
+//! every other hexagon has to cover the same shape moved by its `xy_ref`. Fractional spacings
+//! round the borders of the hexagons differently, so the shapes may differ by one pixel.
+//!
+//! Overlaps and holes can only be found for implementations which override `Hexagons::contains`,
+//! like `PixelHex`. The default `contains` is derived from `axial`, so for `Props` and `HexProps`
+//! only the consistency of the shapes and their rows is checked.
+use std::collections::{BTreeMap, HashSet};
+
+use super::axial::Axial;
+use super::{HexTop, Hexagons};
+
+/// Result of `validate`. All pixel coordinates are integers.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TilingReport {
+    /// Pixels contained in more than one hexagon.
+    pub overlaps: Vec<(i32, i32)>,
+    /// Pixels not contained in any hexagon.
+    pub holes: Vec<(i32, i32)>,
+    /// Pixels where `axial` picks a hexagon which does not contain them
+    /// or whose reference shape does not come within one pixel of them.
+    pub inconsistent: Vec<(i32, i32)>,
+    /// Rows `(y, (x1, x2))` of the reference shape whose ends are more than a pixel away from the
+    /// opposite row rotated by 180° around the center of the shape, which is the center of the widest row.
+    /// For pointy top hexagons these are columns `(x, (y1, y2))`.
+    pub asymmetric_rows: Vec<(i32, (i32, i32))>,
+    /// Rows of the reference shape which are not a single run of pixels, columns for pointy top hexagons.
+    pub split_rows: Vec<i32>,
+}
+
+impl TilingReport {
+    /// True if no problem has been found.
+    pub fn is_valid(&self) -> bool {
+        self.overlaps.is_empty()
+            && self.holes.is_empty()
+            && self.inconsistent.is_empty()
+            && self.asymmetric_rows.is_empty()
+            && self.split_rows.is_empty()
+    }
+}
+
+/// Checks if the reference shape moved to `qr` comes within one pixel of `xy`,
+/// which is as close as the rounding of fractional spacings allows.
+fn covered<H: Hexagons + ?Sized>(hexagons: &H, shape: &HashSet<(i32, i32)>, qr: &Axial, (x, y): (i32, i32)) -> bool {
+    let (xr, yr) = hexagons.xy_ref(qr);
+    let (dx, dy) = ((x as f32 - xr).round() as i32, (y as f32 - yr).round() as i32);
+    shape.contains(&(dx, dy))
+        || (-1..=1).any(|ey| (-1..=1).any(|ex| shape.contains(&(dx + ex, dy + ey))))
+}
+
+/// Pixels mapped to the hexagon `(0, 0)`.
+fn reference_shape<H: Hexagons + ?Sized>(hexagons: &H) -> HashSet<(i32, i32)> {
+    let w = 2 * hexagons.horizontal_spacing().ceil() as i32;
+    let h = 2 * hexagons.vertical_spacing().ceil() as i32;
+    (-h..=h)
+        .flat_map(|y| (-w..=w).map(move |x| (x, y)))
+        .filter(|&(x, y)| hexagons.axial((x as f32, y as f32)) == Axial::default())
+        .collect()
+}
+
+/// Check the rows of the reference shape, or its columns for pointy top hexagons.
+fn check_rows(shape: &HashSet<(i32, i32)>, top: HexTop, report: &mut TilingReport) {
+    let mut rows: BTreeMap<i32, Vec<i32>> = BTreeMap::new();
+    for &(x, y) in shape {
+        match top {
+            HexTop::FLAT => rows.entry(y).or_default().push(x),
+            HexTop::POINTY => rows.entry(x).or_default().push(y),
+        }
+    }
+    let ymin = rows.keys().next().copied().unwrap_or(0);
+    let ymax = rows.keys().last().copied().unwrap_or(0);
+    let extends: BTreeMap<i32, (i32, i32)> = rows.iter_mut()
+        .map(|(y, xs)| {
+            xs.sort_unstable();
+            // the outermost rows can lie on the border, where the rounding alternates between two hexagons
+            let split = (xs[xs.len() - 1] - xs[0]) as usize + 1 != xs.len();
+            if split && *y != ymin && *y != ymax {
+                report.split_rows.push(*y);
+            }
+            (*y, (xs[0], xs[xs.len() - 1]))
+        })
+        .collect();
+
+    // twice the center, so that it stays a whole number
+    let center = extends.values()
+        .max_by_key(|(x1, x2)| x2 - x1)
+        .map_or(0, |(x1, x2)| x1 + x2);
+    for (&y, &(x1, x2)) in &extends {
+        // a hexagon is the same when rotated by 180°, up to the rounding of its borders
+        let mirrored = extends.get(&(ymin + ymax - y))
+            .is_some_and(|(m1, m2)| (m1 - (center - x2)).abs() <= 1 && (m2 - (center - x1)).abs() <= 1);
+        if !mirrored {
+            report.asymmetric_rows.push((y, (x1, x2)));
+        }
+    }
+}
+
+/// Scans all pixels from `min` to `max` (inclusive) and reports where `hexagons`
+/// does not partition the plane into equal shapes.
+/// Overlaps and holes are only reported if `hexagons` overrides `Hexagons::contains`.
+pub fn validate<H: Hexagons + ?Sized>(hexagons: &H, min: (i32, i32), max: (i32, i32)) -> TilingReport {
+    let mut report = TilingReport::default();
+    let shape = reference_shape(hexagons);
+    check_rows(&shape, hexagons.top(), &mut report);
+
+    for y in min.1..=max.1 {
+        for x in min.0..=max.0 {
+            let xy = (x as f32, y as f32);
+            let qr = hexagons.axial(xy);
+            if !covered(hexagons, &shape, &qr, (x, y)) || !hexagons.contains(&qr, xy) {
+                report.inconsistent.push((x, y));
+            }
+            let count = (0..=2)
+                .flat_map(|radius| qr.circle(radius))
+                .filter(|qr| hexagons.contains(qr, xy))
+                .count();
+            match count {
+                0 => report.holes.push((x, y)),
+                1 => {},
+                _ => report.overlaps.push((x, y)),
+            }
+        }
+    }
+    report
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex::Props;
+    use crate::pixelhex::PixelHex;
+
+    #[test]
+    fn test_props() {
+        // fractional spacings only move the borders by a pixel
+        for hexagons in [
+            Props::flat(8f32, 10f32),
+            Props::pointy(9f32, 8f32),
+            Props::flat(7.3f32, 9.7f32),
+            Props::pointy(10.5f32, 7.25f32),
+            Props::flat(21f32, 24f32),
+            Props::pointy(24f32, 21f32),
+        ] {
+            let report = validate(&hexagons, (-40, -40), (40, 40));
+            assert!(report.is_valid(), "{:?}", report);
+            assert!(report.split_rows.is_empty(), "{:?}", report);
+        }
+    }
+
+    #[test]
+    fn test_pixelhex() {
+        let v = vec![
+            (-1.0f32, ( 0f32, 1f32)),
+            ( 0.0f32, (-1f32, 2f32)),
+            ( 1.0f32, (-1f32, 2f32)),
+            ( 2.0f32, ( 0f32, 1f32))
+        ];
+        let report = validate(&PixelHex::flat(&v), (-10, -10), (10, 10));
+        assert!(report.is_valid(), "{:?}", report);
+        let report = validate(&PixelHex::pointy(&v), (-10, -10), (10, 10));
+        assert!(report.is_valid(), "{:?}", report);
+    }
+
+    #[test]
+    fn test_broken() {
+        let v = vec![
+            (-1.0f32, ( 0f32, 1f32)),
+            ( 0.0f32, (-1f32, 2f32)),
+            ( 1.0f32, (-1f32, 1f32)),
+            ( 2.0f32, ( 0f32, 1f32))
+        ];
+        let report = validate(&PixelHex::flat(&v), (-10, -10), (10, 10));
+        assert!(!report.is_valid());
+        assert!(report.holes.contains(&(2, 1)));
+        assert!(report.inconsistent.contains(&(2, 1)));
+        assert!(report.overlaps.is_empty());
+
+        let v = vec![
+            (-1.0f32, ( 0f32, 1f32)),
+            ( 0.0f32, (-1f32, 2f32)),
+            ( 1.0f32, (-1f32, 3f32)),
+            ( 2.0f32, ( 0f32, 1f32))
+        ];
+        let report = validate(&PixelHex::flat(&v), (-10, -10), (10, 10));
+        assert!(report.overlaps.contains(&(3, 1)));
+        assert!(report.holes.is_empty());
+    }
+
+    #[test]
+    fn test_rows() {
+        let mut report = TilingReport::default();
+        let shape: HashSet<(i32, i32)> = [(0, 0), (1, 0), (0, 1), (1, 1), (3, 1), (0, 2), (1, 2), (2, 2)].into_iter().collect();
+        check_rows(&shape, HexTop::FLAT, &mut report);
+        assert_eq!(report.split_rows, vec![1]);
+        assert_eq!(report.asymmetric_rows, vec![(0, (0, 1)), (2, (0, 2))]);
+
+        // the same shape transposed is checked by columns for pointy top hexagons
+        let mut report = TilingReport::default();
+        let shape: HashSet<(i32, i32)> = shape.into_iter().map(|(x, y)| (y, x)).collect();
+        check_rows(&shape, HexTop::POINTY, &mut report);
+        assert_eq!(report.split_rows, vec![1]);
+        assert_eq!(report.asymmetric_rows, vec![(0, (0, 1)), (2, (0, 2))]);
+        let mut report = TilingReport::default();
+        check_rows(&shape, HexTop::FLAT, &mut report);
+        assert!(report.split_rows.is_empty());
+    }
+}
@@ -0,0 +1,259 @@
+//! Line of sight and field of view between hexagons.
+//!
+//! Visibility is based on hex line drawing. Lines running exactly along an edge
+//! touch two hexagons at once, `EdgeRule` decides which of them must be transparent.
+use std::collections::HashSet;
+use std::f64::consts::{PI, TAU};
+
+use super::axial::{Axial, LineToHex, LINE_NUDGE};
+
+/// How lines along edges and through corners are treated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum EdgeRule {
+    /// Every hexagon touched by the line must be transparent, like `Axial::supercover_to`.
+    Strict,
+    /// The line is nudged to both sides and one of them must be clear.
+    #[default]
+    Permissive,
+}
+
+/// The line from `from` to `to`, nudged to both sides.
+fn nudged_lines(from: Axial, to: Axial) -> (LineToHex, LineToHex) {
+    (LineToHex::new(from, to, LINE_NUDGE), LineToHex::new(from, to, (-LINE_NUDGE.0, -LINE_NUDGE.1)))
+}
+
+/// Returns true if all hexagons of `line`, except the first and the last, are transparent.
+fn clear(line: LineToHex, is_opaque: &mut impl FnMut(Axial) -> bool) -> bool {
+    let n = line.len();
+    line.skip(1).take(n.saturating_sub(2)).all(|qr| !is_opaque(qr))
+}
+
+/// Returns true if `to` can be seen from `from`.
+/// The ends themselves may be opaque, so walls are visible but hide what lies behind them.
+/// The result does not depend on the order of `from` and `to`.
+pub fn line_of_sight<F>(from: Axial, to: Axial, rule: EdgeRule, mut is_opaque: F) -> bool
+where
+    F: FnMut(Axial) -> bool,
+{
+    let (plus, minus) = nudged_lines(from, to);
+    match rule {
+        EdgeRule::Strict => clear(plus, &mut is_opaque) && clear(minus, &mut is_opaque),
+        EdgeRule::Permissive => clear(plus, &mut is_opaque) || clear(minus, &mut is_opaque),
+    }
+}
+
+/// Distance from a hexagon center within which lines can pass through the hexagon,
+/// in the units of `plane_xy`. The circumradius is `1 / sqrt(3)`, the rest covers the nudge.
+const HEX_REACH: f64 = 0.6;
+
+/// Position of `qr` relative to `center` in a plane where neighbours are 1 apart.
+fn plane_xy(center: Axial, qr: Axial) -> (f64, f64) {
+    let (q, r) = qr.to_tuple();
+    let (cq, cr) = center.to_tuple();
+    let (dq, dr) = (q as f64 - cq as f64, r as f64 - cr as f64);
+    (dq + 0.5 * dr, dr * 0.75f64.sqrt())
+}
+
+/// The distances of the opaque hexagons, sorted into sectors around the center
+/// by the directions of the lines which can pass through them.
+struct Shadows {
+    sectors: Vec<Vec<u32>>,
+}
+
+impl Shadows {
+    fn new(radius: u32) -> Self {
+        Shadows { sectors: vec![Vec::new(); 6 * radius.max(1) as usize] }
+    }
+
+    /// Sector of `angle`, not yet wrapped into the range of `sectors`.
+    fn sector(&self, angle: f64) -> i64 {
+        ((angle + PI) / TAU * self.sectors.len() as f64).floor() as i64
+    }
+
+    fn index(&self, sector: i64) -> usize {
+        sector.rem_euclid(self.sectors.len() as i64) as usize
+    }
+
+    /// Adds an opaque hexagon at `xy` which is `distance` away from the center.
+    fn add(&mut self, (x, y): (f64, f64), distance: u32) {
+        let angle = y.atan2(x);
+        let spread = (HEX_REACH / x.hypot(y)).min(1f64).asin();
+        let (first, last) = (self.sector(angle - spread), self.sector(angle + spread));
+        let n = (last - first + 1).min(self.sectors.len() as i64);
+        for sector in first..first + n {
+            let i = self.index(sector);
+            self.sectors[i].push(distance);
+        }
+    }
+
+    /// Distances below `max` of the opaque hexagons which the line from the center to `xy` might pass.
+    fn distances(&self, (x, y): (f64, f64), max: u32) -> Vec<u32> {
+        let mut distances: Vec<u32> = self.sectors[self.index(self.sector(y.atan2(x)))].iter()
+            .copied()
+            .filter(|&d| d < max)
+            .collect();
+        distances.sort_unstable();
+        distances.dedup();
+        distances
+    }
+}
+
+/// Returns true if the hexagons of `line` at `distances` are transparent.
+fn clear_at(line: &LineToHex, distances: &[u32], opaque: &HashSet<Axial>) -> bool {
+    distances.iter().all(|&d| !opaque.contains(&line.nth_hex(d)))
+}
+
+/// All hexagons within `radius` of `center` which are in line of sight, including `center`.
+/// Visibility is symmetric: if `b` is in the field of view of `a`, then `a` is in the
+/// field of view of `b` for the same radius, rule and opaque hexagons.
+/// `is_opaque` is called once for every hexagon closer than `radius`.
+///
+/// The result is the same as checking every hexagon in range with `line_of_sight`.
+/// The hexagon `d` steps along a line is always `d` away from `center`, so the hexagons are
+/// visited ring by ring and every opaque one casts a shadow over the directions it covers.
+/// Lines are only followed in shadows, and only at the distances of the hexagons casting them.
+/// Open areas cost O(radius²).
+pub fn field_of_view<F>(center: Axial, radius: u32, rule: EdgeRule, mut is_opaque: F) -> HashSet<Axial>
+where
+    F: FnMut(Axial) -> bool,
+{
+    let mut opaque = HashSet::new();
+    let mut shadows = Shadows::new(radius);
+    let mut visible = HashSet::new();
+    for qr in center.spiral(radius) {
+        let distance = qr.distance_to(center);
+        let xy = plane_xy(center, qr);
+        let distances = shadows.distances(xy, distance);
+        let seen = distances.is_empty() || {
+            let (plus, minus) = nudged_lines(center, qr);
+            match rule {
+                EdgeRule::Strict => clear_at(&plus, &distances, &opaque) && clear_at(&minus, &distances, &opaque),
+                EdgeRule::Permissive => clear_at(&plus, &distances, &opaque) || clear_at(&minus, &distances, &opaque),
+            }
+        };
+        if seen {
+            visible.insert(qr);
+        }
+        // the center and the last ring are never between two hexagons
+        if distance > 0 && distance < radius && is_opaque(qr) {
+            opaque.insert(qr);
+            shadows.add(xy, distance);
+        }
+    }
+    visible
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(visible: &HashSet<Axial>, walls: &[Axial], radius: u32) -> Vec<String> {
+        (-(radius as i32)..=radius as i32).map(|r| {
+            (-(radius as i32)..=radius as i32)
+                .map(|q| Axial::new(q, r))
+                .filter(|qr| qr.length() <= radius)
+                .map(|qr| match (walls.contains(&qr), visible.contains(&qr)) {
+                    (true, true) => '#',
+                    (true, false) => '%',
+                    (false, true) => '.',
+                    (false, false) => ' ',
+                })
+                .collect()
+        }).collect()
+    }
+
+    #[test]
+    fn test_line_of_sight() {
+        let wall = Axial::new(1, 0);
+        let opaque = |qr: Axial| qr == wall;
+        let origin = Axial::default();
+        assert!(line_of_sight(origin, wall, EdgeRule::Strict, opaque));
+        assert!(!line_of_sight(origin, Axial::new(2, 0), EdgeRule::Permissive, opaque));
+        assert!(!line_of_sight(origin, Axial::new(3, 0), EdgeRule::Permissive, opaque));
+        // the line to (2, -1) runs along the edge between (1, 0) and (1, -1)
+        assert!(line_of_sight(origin, Axial::new(2, -1), EdgeRule::Permissive, opaque));
+        assert!(!line_of_sight(origin, Axial::new(2, -1), EdgeRule::Strict, opaque));
+        // the line to (1, 1) runs through the corner between (1, 0) and (0, 1)
+        assert!(!line_of_sight(origin, Axial::new(1, 1), EdgeRule::Strict, opaque));
+        assert!(line_of_sight(origin, Axial::new(1, 1), EdgeRule::Permissive, opaque));
+        assert!(line_of_sight(origin, Axial::new(0, 2), EdgeRule::Strict, opaque));
+        assert!(line_of_sight(origin, origin, EdgeRule::Strict, |_| true));
+        assert!(line_of_sight(origin, Axial::new(0, 1), EdgeRule::Strict, |_| true));
+    }
+
+    #[test]
+    fn test_known_layout() {
+        let walls = [Axial::new(1, 0), Axial::new(1, -1), Axial::new(-2, 2)];
+        let visible = field_of_view(Axial::default(), 3, EdgeRule::Strict, |qr| walls.contains(&qr));
+        assert_eq!(render(&visible, &walls, 3), vec![
+            "..  ",
+            "..   ",
+            "...#  ",
+            "....#  ",
+            "....  ",
+            ".#...",
+            " ...",
+        ]);
+        let visible = field_of_view(Axial::default(), 3, EdgeRule::Permissive, |qr| walls.contains(&qr));
+        assert_eq!(render(&visible, &walls, 3), vec![
+            "..  ",
+            "...  ",
+            "...#  ",
+            "....#  ",
+            "..... ",
+            ".#...",
+            " ...",
+        ]);
+        let open = field_of_view(Axial::new(5, -2), 4, EdgeRule::Strict, |_| false);
+        assert_eq!(open.len(), Axial::new(5, -2).range(4).len());
+    }
+
+    /// `field_of_view` by checking every hexagon in range.
+    fn reference(center: Axial, radius: u32, rule: EdgeRule, is_opaque: impl Fn(Axial) -> bool) -> HashSet<Axial> {
+        center.range(radius).filter(|qr| line_of_sight(center, *qr, rule, &is_opaque)).collect()
+    }
+
+    #[test]
+    fn test_reference() {
+        let layouts: Vec<Box<dyn Fn(Axial) -> bool>> = vec![
+            Box::new(|_| false),
+            Box::new(|qr: Axial| qr.length() == 4),
+            Box::new(|qr: Axial| { let (q, r) = qr.to_tuple(); (q * 7 + r * 13).rem_euclid(5) == 0 }),
+            Box::new(|qr: Axial| { let (q, r) = qr.to_tuple(); (q * q * 3 + r * 5 + q * r).rem_euclid(11) < 2 }),
+            Box::new(|qr: Axial| { let (q, r) = qr.to_tuple(); q == 2 || (r == -3 && q < 4) }),
+        ];
+        for is_opaque in &layouts {
+            for rule in [EdgeRule::Strict, EdgeRule::Permissive] {
+                for (center, radius) in [(Axial::default(), 12), (Axial::new(-3, 5), 9), (Axial::new(2, -1), 1), (Axial::new(2, 0), 0)] {
+                    let mut calls = HashSet::new();
+                    let visible = field_of_view(center, radius, rule, |qr| {
+                        assert!(calls.insert(qr), "{:?} queried twice", qr);
+                        is_opaque(qr)
+                    });
+                    assert_eq!(visible, reference(center, radius, rule, is_opaque), "{:?} {:?} {}", rule, center, radius);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_symmetry() {
+        // a scattered set of pillars
+        let opaque = |qr: Axial| {
+            let (q, r) = qr.to_tuple();
+            (q * 7 + r * 13).rem_euclid(5) == 0 && qr.length() > 0
+        };
+        for rule in [EdgeRule::Strict, EdgeRule::Permissive] {
+            let radius = 5;
+            let center = Axial::default();
+            let visible = field_of_view(center, radius, rule, opaque);
+            assert!(visible.contains(&center));
+            for qr in center.range(radius) {
+                let back = field_of_view(qr, radius, rule, opaque);
+                assert_eq!(visible.contains(&qr), back.contains(&center), "{:?} {:?}", rule, qr);
+                assert_eq!(line_of_sight(center, qr, rule, opaque), line_of_sight(qr, center, rule, opaque));
+            }
+        }
+    }
+}